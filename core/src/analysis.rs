@@ -0,0 +1,450 @@
+//! Nullable, FIRST and FOLLOW sets computed over the EBNF constructs.
+//!
+//! Terminals follow the same convention as [crate::SyntaxRef::iter_terminals]:
+//! literals, special sequences, and meta identifiers which are not defined by
+//! any rule. A literal and a terminal of the same name are distinct
+//! lookaheads.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{
+    prelude::*, syntax::to_syntax, DefinitionsList, MetaIdentifier, Primary, SingleDefinition,
+    Syntax, Term,
+};
+
+/// A terminal which can be looked ahead by a parser.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Lookahead {
+    /// The text of a literal.
+    Literal(String),
+    /// The name of an undefined meta identifier, or the text of a special
+    /// sequence.
+    Terminal(String),
+    /// The end of the input.
+    End,
+}
+
+impl Lookahead {
+    /// Returns the lookaheads a token of a stream can stand for, in the order
+    /// the drivers try them: the text of a literal, then the name of a
+    /// terminal. None is the end of the input.
+    pub fn of_token(token: Option<&str>) -> Vec<Self> {
+        match token {
+            Some(token) => vec![
+                Lookahead::Literal(token.to_string()),
+                Lookahead::Terminal(token.to_string()),
+            ],
+            None => vec![Lookahead::End],
+        }
+    }
+
+    /// Returns the text of the literal or the name of the terminal, None for
+    /// the end of the input.
+    pub fn as_terminal(&self) -> Option<&str> {
+        match self {
+            Lookahead::Literal(value) | Lookahead::Terminal(value) => Some(value),
+            Lookahead::End => None,
+        }
+    }
+
+    pub fn borrow(&self) -> LookaheadRef<'_> {
+        match self {
+            Lookahead::Literal(value) => LookaheadRef::Literal(value),
+            Lookahead::Terminal(name) => LookaheadRef::Terminal(name),
+            Lookahead::End => LookaheadRef::End,
+        }
+    }
+}
+
+impl Display for Lookahead {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lookahead::Literal(value) => write!(f, "{value:?}"),
+            Lookahead::Terminal(name) => write!(f, "<{name}>"),
+            Lookahead::End => write!(f, "$"),
+        }
+    }
+}
+
+impl quote::ToTokens for Lookahead {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        tokens.extend(match self {
+            Lookahead::Literal(value) => {
+                quote! { ::pb_ebnf::analysis::LookaheadRef::Literal(#value) }
+            }
+            Lookahead::Terminal(name) => {
+                quote! { ::pb_ebnf::analysis::LookaheadRef::Terminal(#name) }
+            }
+            Lookahead::End => quote! { ::pb_ebnf::analysis::LookaheadRef::End },
+        })
+    }
+}
+
+/// A lookahead borrowing its text, as emitted by the `ebnf!` macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LookaheadRef<'a> {
+    Literal(&'a str),
+    Terminal(&'a str),
+    End,
+}
+
+impl<'a> LookaheadRef<'a> {
+    /// Returns the text of the literal or the name of the terminal, None for
+    /// the end of the input.
+    pub fn as_terminal(self) -> Option<&'a str> {
+        match self {
+            LookaheadRef::Literal(value) | LookaheadRef::Terminal(value) => Some(value),
+            LookaheadRef::End => None,
+        }
+    }
+
+    pub fn to_owned(self) -> Lookahead {
+        match self {
+            LookaheadRef::Literal(value) => Lookahead::Literal(value.to_string()),
+            LookaheadRef::Terminal(name) => Lookahead::Terminal(name.to_string()),
+            LookaheadRef::End => Lookahead::End,
+        }
+    }
+}
+
+pub type LookaheadSet = BTreeSet<Lookahead>;
+
+/// Nullable, FIRST and FOLLOW sets of every rule of a syntax.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    start: Option<String>,
+    /// The lhs of the rules, in the order of their first definition.
+    order: Vec<String>,
+    rules: HashMap<String, DefinitionsList>,
+    nullable: HashSet<String>,
+    first: HashMap<String, LookaheadSet>,
+    follow: HashMap<String, LookaheadSet>,
+}
+
+impl Analysis {
+    /// Analyses the syntax, the start symbol being the lhs of the first rule.
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let syntax = to_syntax(syntax);
+        let start = syntax.first().map(|rule| rule.lhs.to_string());
+        Self::build(syntax, start)
+    }
+
+    /// Analyses the syntax from the given start symbol.
+    pub fn with_start<S: ISyntax>(syntax: &S, start: &str) -> Self {
+        Self::build(to_syntax(syntax), Some(start.to_string()))
+    }
+
+    fn build(syntax: Syntax, start: Option<String>) -> Self {
+        // Several rules with the same lhs are seen as alternatives of a single one.
+        let mut order = Vec::<String>::default();
+        let mut rules = HashMap::<String, DefinitionsList>::default();
+
+        for rule in syntax {
+            let rhs = rules.entry(rule.lhs.to_string()).or_insert_with(|| {
+                order.push(rule.lhs.to_string());
+                DefinitionsList::default()
+            });
+
+            rhs.extend(rule.rhs);
+        }
+
+        let mut analysis = Self {
+            start,
+            order,
+            rules,
+            ..Default::default()
+        };
+
+        analysis.compute_nullable();
+        analysis.compute_first();
+        analysis.compute_follow();
+        analysis
+    }
+
+    /// Returns the start symbol, used to seed the FOLLOW sets.
+    pub fn start(&self) -> Option<&str> {
+        self.start.as_deref()
+    }
+
+    /// Returns true if the symbol is defined by a rule.
+    pub fn is_defined(&self, rule: &str) -> bool {
+        self.rules.contains_key(rule)
+    }
+
+    /// Returns the definitions list of the rule, merged if the rule is
+    /// defined several times.
+    pub fn rhs(&self, rule: &str) -> Option<&DefinitionsList> {
+        self.rules.get(rule)
+    }
+
+    /// Iterates over the defined rules, in the order of their first
+    /// definition in the syntax.
+    pub fn iter_rules(&self) -> impl Iterator<Item = (&str, &DefinitionsList)> {
+        self.order
+            .iter()
+            .map(|lhs| (lhs.as_str(), &self.rules[lhs]))
+    }
+
+    /// Returns true if the rule can derive the empty string.
+    pub fn is_nullable(&self, rule: &str) -> bool {
+        self.nullable.contains(rule)
+    }
+
+    /// Returns the terminals which can start a derivation of the rule.
+    pub fn first(&self, rule: &str) -> Option<&LookaheadSet> {
+        self.first.get(rule)
+    }
+
+    /// Returns the terminals which can follow the rule, including the end of
+    /// the input for the start symbol.
+    pub fn follow(&self, rule: &str) -> Option<&LookaheadSet> {
+        self.follow.get(rule)
+    }
+
+    pub fn is_nullable_definitions_list(&self, defs: &DefinitionsList) -> bool {
+        defs.iter()
+            .any(|def| self.is_nullable_single_definition(def))
+    }
+
+    pub fn is_nullable_single_definition(&self, def: &SingleDefinition) -> bool {
+        def.iter().all(|term| self.is_nullable_term(term))
+    }
+
+    pub fn is_nullable_term(&self, term: &Term) -> bool {
+        term.repetition == Some(0) || self.is_nullable_primary(&term.primary)
+    }
+
+    pub fn is_nullable_primary(&self, primary: &Primary) -> bool {
+        match primary {
+            Primary::Optional(_) | Primary::Repeated(_) | Primary::Empty => true,
+            Primary::Grouped(seq) => self.is_nullable_definitions_list(seq),
+            Primary::MetaIdentifier(id) => self.is_nullable(id),
+            Primary::Literal(_) | Primary::Special(_) => false,
+        }
+    }
+
+    pub fn first_of_definitions_list(&self, defs: &DefinitionsList) -> LookaheadSet {
+        defs.iter()
+            .flat_map(|def| self.first_of_single_definition(def))
+            .collect()
+    }
+
+    pub fn first_of_single_definition(&self, def: &SingleDefinition) -> LookaheadSet {
+        self.first_of_terms(def)
+    }
+
+    /// Returns the FIRST set of a sequence of terms.
+    pub fn first_of_terms(&self, terms: &[Term]) -> LookaheadSet {
+        let mut set = LookaheadSet::default();
+
+        for term in terms {
+            set.extend(self.first_of_term(term));
+
+            if !self.is_nullable_term(term) {
+                break;
+            }
+        }
+
+        set
+    }
+
+    pub fn first_of_term(&self, term: &Term) -> LookaheadSet {
+        if term.repetition == Some(0) {
+            LookaheadSet::default()
+        } else {
+            self.first_of_primary(&term.primary)
+        }
+    }
+
+    pub fn first_of_primary(&self, primary: &Primary) -> LookaheadSet {
+        match primary {
+            Primary::Optional(seq) => self.first_of_definitions_list(seq),
+            Primary::Repeated(seq) => self.first_of_definitions_list(seq.as_ref()),
+            Primary::Grouped(seq) => self.first_of_definitions_list(seq),
+            Primary::MetaIdentifier(id) if self.is_defined(id) => {
+                self.first(id).cloned().unwrap_or_default()
+            }
+            Primary::MetaIdentifier(id) => LookaheadSet::from([terminal(id)]),
+            Primary::Literal(lit) => LookaheadSet::from([Lookahead::Literal(lit.to_string())]),
+            Primary::Special(seq) => LookaheadSet::from([terminal(seq)]),
+            Primary::Empty => LookaheadSet::default(),
+        }
+    }
+
+    /// Returns the transitive symbols of the rule.
+    ///
+    /// Transitive symbols are the meta identifiers a derivation of the rule
+    /// can start with, every symbol before them being able to derive the
+    /// empty string.
+    ///
+    /// Example :
+    /// <foo> = [<bar>], <acme>;
+    ///
+    /// Both <bar> and <acme> are transitive symbols of <foo>.
+    pub fn transitive(&self, rule: &str) -> Vec<&MetaIdentifier> {
+        self.rhs(rule)
+            .map(|rhs| self.transitive_of_definitions_list(rhs))
+            .unwrap_or_default()
+    }
+
+    pub fn transitive_of_definitions_list<'a>(
+        &self,
+        defs: &'a DefinitionsList,
+    ) -> Vec<&'a MetaIdentifier> {
+        let mut symbols = Vec::default();
+
+        for def in defs.iter() {
+            self.transitive_of_single_definition(def, &mut symbols);
+        }
+
+        symbols
+    }
+
+    fn transitive_of_single_definition<'a>(
+        &self,
+        def: &'a SingleDefinition,
+        symbols: &mut Vec<&'a MetaIdentifier>,
+    ) {
+        for term in def.iter() {
+            if term.repetition != Some(0) {
+                match &term.primary {
+                    Primary::MetaIdentifier(id) => symbols.push(id),
+                    primary => {
+                        if let Some(seq) = primary.try_as_definitions_list() {
+                            symbols.extend(self.transitive_of_definitions_list(seq));
+                        }
+                    }
+                }
+            }
+
+            if !self.is_nullable_term(term) {
+                break;
+            }
+        }
+    }
+
+    fn compute_nullable(&mut self) {
+        loop {
+            let nullable = self
+                .rules
+                .iter()
+                .filter(|(_, rhs)| self.is_nullable_definitions_list(rhs))
+                .map(|(lhs, _)| lhs.clone())
+                .collect::<HashSet<_>>();
+
+            if nullable.len() == self.nullable.len() {
+                break;
+            }
+
+            self.nullable = nullable;
+        }
+    }
+
+    fn compute_first(&mut self) {
+        loop {
+            let first = self
+                .rules
+                .iter()
+                .map(|(lhs, rhs)| (lhs.clone(), self.first_of_definitions_list(rhs)))
+                .collect::<HashMap<_, _>>();
+
+            if first == self.first {
+                break;
+            }
+
+            self.first = first;
+        }
+    }
+
+    fn compute_follow(&mut self) {
+        self.follow = self
+            .rules
+            .keys()
+            .map(|lhs| (lhs.clone(), LookaheadSet::default()))
+            .collect();
+
+        if let Some(set) = self
+            .start
+            .as_ref()
+            .and_then(|start| self.follow.get_mut(start))
+        {
+            set.insert(Lookahead::End);
+        }
+
+        loop {
+            let mut follow = self.follow.clone();
+
+            for (lhs, rhs) in self.rules.iter() {
+                let trailer = self.follow[lhs].clone();
+                self.follow_of_definitions_list(rhs, &trailer, &mut follow);
+            }
+
+            if follow == self.follow {
+                break;
+            }
+
+            self.follow = follow;
+        }
+    }
+
+    /// Propagates the terminals which can follow the definitions list to its
+    /// symbols.
+    fn follow_of_definitions_list(
+        &self,
+        defs: &DefinitionsList,
+        trailer: &LookaheadSet,
+        follow: &mut HashMap<String, LookaheadSet>,
+    ) {
+        for def in defs.iter() {
+            let mut trailer = trailer.clone();
+
+            for term in def.iter().rev() {
+                let mut inner = trailer.clone();
+
+                // n * X, every X but the last is followed by another X.
+                if term.repetition.is_some_and(|n| n > 1) {
+                    inner.extend(self.first_of_primary(&term.primary));
+                }
+
+                self.follow_of_primary(&term.primary, &inner, follow);
+
+                if self.is_nullable_term(term) {
+                    trailer.extend(self.first_of_term(term));
+                } else {
+                    trailer = self.first_of_term(term);
+                }
+            }
+        }
+    }
+
+    fn follow_of_primary(
+        &self,
+        primary: &Primary,
+        trailer: &LookaheadSet,
+        follow: &mut HashMap<String, LookaheadSet>,
+    ) {
+        match primary {
+            Primary::Optional(seq) => self.follow_of_definitions_list(seq, trailer, follow),
+            Primary::Grouped(seq) => self.follow_of_definitions_list(seq, trailer, follow),
+            // The sequence can be followed by another iteration of itself.
+            Primary::Repeated(seq) => {
+                let mut trailer = trailer.clone();
+                trailer.extend(self.first_of_definitions_list(seq.as_ref()));
+                self.follow_of_definitions_list(seq.as_ref(), &trailer, follow)
+            }
+            Primary::MetaIdentifier(id) => {
+                if let Some(set) = follow.get_mut(&**id) {
+                    set.extend(trailer.iter().cloned());
+                }
+            }
+            Primary::Literal(_) | Primary::Special(_) | Primary::Empty => {}
+        }
+    }
+}
+
+fn terminal(value: &str) -> Lookahead {
+    Lookahead::Terminal(value.to_string())
+}
@@ -1,6 +1,9 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
 
 use crate::syntax::Syntax;
+use crate::visit::{has_exception, walk_primaries};
 use crate::{DefinitionsList, IntoPrimary, Primary, Rule, SingleDefinition, Term};
 
 use pb_bnf::definition::Definition as BnfDefinition;
@@ -8,34 +11,229 @@ use pb_bnf::definition_set::DefinitionSet as BnfDefinitionSet;
 use pb_bnf::literal::Literal as BnfLiteral;
 use pb_bnf::rule::Rule as BnfRule;
 use pb_bnf::symbol::Symbol;
-use pb_bnf::syntax::Syntax as BnfSyntax;
-use pb_bnf::term::Term as BnfTerm;
+pub use pb_bnf::syntax::Syntax as BnfSyntax;
+pub use pb_bnf::term::Term as BnfTerm;
 
-impl From<Syntax> for BnfSyntax {
-    fn from(value: Syntax) -> Self {
-        let mut ctx = Converter::default();
+impl TryFrom<Syntax> for BnfSyntax {
+    type Error = LoweringError;
 
-        let mut_ref_ctx = &mut ctx;
+    fn try_from(value: Syntax) -> Result<Self, Self::Error> {
+        BnfLowering::try_from(value).map(|lowering| lowering.syntax)
+    }
+}
+
+/// An error raised while lowering a syntax to BNF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoweringError {
+    /// The rule contains an exception, which BNF cannot express.
+    Exception(String),
+}
+
+impl Display for LoweringError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoweringError::Exception(rule) => {
+                write!(
+                    f,
+                    "<{rule}> contains an exception, which BNF cannot express"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoweringError {}
+
+/// How repeated sequences are lowered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Recursion {
+    /// { E } becomes X = ε | X E, which suits LR parsers.
+    #[default]
+    Left,
+    /// { E } becomes X = ε | E X, which suits LL parsers.
+    Right,
+}
+
+/// Options of the BNF lowering, see [BnfLowering::with_options].
+#[derive(Debug, Clone, Default)]
+pub struct BnfLoweringOptions {
+    repetition: Recursion,
+    inline_optionals: bool,
+    deduplicate: bool,
+}
+
+impl BnfLoweringOptions {
+    /// Lowers the repeated sequences to left-recursive rules, by default, or
+    /// to right-recursive ones.
+    pub fn repetition(mut self, recursion: Recursion) -> Self {
+        self.repetition = recursion;
+        self
+    }
+
+    /// Inlines the optional sequences, `A [E] B` becoming `A E B | A B`
+    /// instead of `A X B` with `X = ε | E`.
+    ///
+    /// The number of alternatives doubles with each optional sequence of a
+    /// single definition.
+    pub fn inline_optionals(mut self, inline: bool) -> Self {
+        self.inline_optionals = inline;
+        self
+    }
+
+    /// Shares one anonymous rule between the identical sequences, whose
+    /// origin is the first one.
+    pub fn deduplicate(mut self, deduplicate: bool) -> Self {
+        self.deduplicate = deduplicate;
+        self
+    }
+}
+
+/// The BNF lowering of a syntax, along with where its anonymous rules come
+/// from.
+///
+/// Anonymous rules are named after the EBNF rule and the sequence they are
+/// created for, eg. `Statement__opt_1` for the second anonymous rule of
+/// `<Statement>`, an optional sequence. The index is bumped until the name
+/// clashes with no rule, meta identifier or special sequence of the syntax.
+#[derive(Debug, Clone, Default)]
+pub struct BnfLowering {
+    pub syntax: BnfSyntax,
+    /// Where each anonymous rule has been created, by name.
+    pub origins: HashMap<String, Origin>,
+}
+
+/// The term of an EBNF rule for which an anonymous rule has been created.
+///
+/// The sequences nested in a sequence have the origin of the outermost one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub rule: String,
+    /// Index of the alternative in the definitions list of the rule.
+    pub alternative: usize,
+    /// Index of the term in the alternative.
+    pub term: usize,
+}
+
+impl BnfLowering {
+    /// Lowers the syntax, unless it contains an exception: the language of
+    /// `A - B` is not context-free in general, and dropping the exception
+    /// would accept what it rejects.
+    pub fn with_options(
+        syntax: Syntax,
+        options: BnfLoweringOptions,
+    ) -> Result<Self, LoweringError> {
+        if let Some(rule) = syntax.iter().find(|rule| has_exception(&rule.rhs)) {
+            return Err(LoweringError::Exception(rule.lhs.to_string()));
+        }
+
+        let mut ctx = Converter {
+            options,
+            ..Converter::default()
+        };
+
+        for rule in syntax.iter() {
+            ctx.reserved.insert(rule.lhs.to_string());
+
+            walk_primaries(&rule.rhs, &mut |primary| match primary {
+                Primary::MetaIdentifier(id) => {
+                    ctx.reserved.insert(id.to_string());
+                }
+                Primary::Special(seq) => {
+                    ctx.reserved.insert(seq.to_string());
+                }
+                _ => {}
+            });
+        }
+
+        for rule in syntax.into_iter() {
+            convert_rule(rule, &mut ctx);
+        }
+
+        Ok(BnfLowering {
+            syntax: ctx.syntax,
+            origins: ctx.origins,
+        })
+    }
+
+    /// Returns the EBNF rule a BNF rule comes from, which is the rule itself
+    /// unless it is anonymous.
+    pub fn origin<'a>(&'a self, name: &'a str) -> &'a str {
+        self.origins
+            .get(name)
+            .map_or(name, |origin| origin.rule.as_str())
+    }
+}
 
-        value
-            .into_iter()
-            .for_each(move |rule| convert_rule(rule, mut_ref_ctx));
+impl TryFrom<Syntax> for BnfLowering {
+    type Error = LoweringError;
 
-        ctx.syntax
+    /// Lowers the syntax with the default options.
+    fn try_from(value: Syntax) -> Result<Self, Self::Error> {
+        Self::with_options(value, BnfLoweringOptions::default())
     }
 }
 
 #[derive(Default)]
 struct Converter {
+    options: BnfLoweringOptions,
     syntax: BnfSyntax,
-    anonymous_counter: u32,
+    /// The symbols of the syntax, and the anonymous rules created so far.
+    reserved: HashSet<String>,
+    /// Anonymous rules created so far in each EBNF rule.
+    counters: HashMap<String, usize>,
+    /// The term of the EBNF rule being converted.
+    origin: Option<Origin>,
+    origins: HashMap<String, Origin>,
+    /// The anonymous rules, by role and sequence, if they are shared.
+    shared: HashMap<String, Symbol>,
 }
 
 impl Converter {
-    pub fn new_anonymous_rule_name(&mut self) -> Symbol {
-        let id = self.anonymous_counter;
-        self.anonymous_counter += 1;
-        Symbol::from(format!("a{id}"))
+    /// Returns a fresh name for the sequence, role being one of opt, rep or
+    /// grp.
+    pub fn new_anonymous_rule_name(&mut self, role: &str) -> Symbol {
+        let origin = self.origin.clone().unwrap();
+        let counter = self.counters.entry(origin.rule.clone()).or_default();
+
+        let name = loop {
+            let name = format!("{}__{role}_{counter}", origin.rule);
+            *counter += 1;
+
+            if self.reserved.insert(name.clone()) {
+                break name;
+            }
+        };
+
+        self.origins.insert(name.clone(), origin);
+        Symbol::from(name)
+    }
+
+    /// Returns the anonymous rule of the sequence, created with the given
+    /// function unless an identical one is shared.
+    pub fn anonymous_rule<F>(&mut self, role: &str, defs: DefinitionsList, create: F) -> Symbol
+    where
+        F: FnOnce(&mut Self, Symbol, DefinitionsList) -> BnfRule,
+    {
+        let key = format!("{role} {defs}");
+
+        if let Some(symbol) = self
+            .options
+            .deduplicate
+            .then(|| self.shared.get(&key))
+            .flatten()
+        {
+            return symbol.clone();
+        }
+
+        let symbol = self.new_anonymous_rule_name(role);
+
+        if self.options.deduplicate {
+            self.shared.insert(key, symbol.clone());
+        }
+
+        let rule = create(self, symbol.clone(), defs);
+        self.push(rule);
+        symbol
     }
 }
 
@@ -54,66 +252,150 @@ impl DerefMut for Converter {
 }
 
 fn convert_rule(rule: Rule, ctx: &mut Converter) {
-    let rhs = convert_definitions_list(rule.rhs, ctx);
-    ctx.push(BnfRule::new(rule.lhs.into_symbol(), rhs));
+    let name = rule.lhs.to_string();
+    // Inserted before the anonymous rules created for it, so that the first
+    // rule stays the start one.
+    let index = ctx.len();
+    let mut rhs = BnfDefinitionSet::default();
+
+    for (alternative, def) in rule.rhs.into_iter().enumerate() {
+        let mut defs = vec![BnfDefinition::default()];
+
+        for (term, value) in def.into_iter().enumerate() {
+            ctx.origin = Some(Origin {
+                rule: name.clone(),
+                alternative,
+                term,
+            });
+            defs = concat(defs, &convert_term(value, ctx));
+        }
+
+        push_definitions(&mut rhs, defs);
+    }
+
+    ctx.insert(index, BnfRule::new(rule.lhs.into_symbol(), rhs));
 }
 
 fn convert_definitions_list(defs: DefinitionsList, ctx: &mut Converter) -> BnfDefinitionSet {
-    defs.into_iter()
-        .map(|def| convert_single_definition(def, ctx))
-        .collect()
+    let mut set = BnfDefinitionSet::default();
+
+    for def in defs.into_iter() {
+        let defs = convert_single_definition(def, ctx);
+        push_definitions(&mut set, defs);
+    }
+
+    set
 }
 
-fn convert_single_definition(def: SingleDefinition, ctx: &mut Converter) -> BnfDefinition {
+/// Returns the alternatives of the single definition, more than one if an
+/// optional sequence is inlined.
+fn convert_single_definition(def: SingleDefinition, ctx: &mut Converter) -> Vec<BnfDefinition> {
     def.into_iter()
-        .flat_map(|term| convert_term(term, ctx))
-        .collect()
+        .fold(vec![BnfDefinition::default()], |defs, term| {
+            concat(defs, &convert_term(term, ctx))
+        })
+}
+
+/// Adds the alternatives which are not already in the set.
+fn push_definitions(set: &mut Vec<BnfDefinition>, defs: Vec<BnfDefinition>) {
+    for def in defs {
+        if !set.contains(&def) {
+            set.push(def);
+        }
+    }
+}
+
+/// Returns every alternative of the prefixes followed by every alternative
+/// of the suffixes.
+fn concat(prefixes: Vec<BnfDefinition>, suffixes: &[BnfDefinition]) -> Vec<BnfDefinition> {
+    let mut defs = Vec::<BnfDefinition>::default();
+
+    for prefix in prefixes.iter() {
+        for suffix in suffixes.iter() {
+            let def = prefix.iter().chain(suffix.iter()).cloned().collect();
+
+            if !defs.contains(&def) {
+                defs.push(def);
+            }
+        }
+    }
+
+    defs
 }
 
-fn convert_term(term: Term, ctx: &mut Converter) -> impl Iterator<Item = BnfTerm> {
+/// Returns the alternatives of the term, see [convert_single_definition].
+///
+/// The term has no exception, see [BnfLowering::try_from].
+fn convert_term(term: Term, ctx: &mut Converter) -> Vec<BnfDefinition> {
     let n = term.repetition.unwrap_or(1);
-    let term = convert_primary(term.into_primary(), ctx);
 
-    std::iter::repeat(term).take(n as usize).flatten()
+    let alternatives = match term.into_primary() {
+        // A [ E ] B becomes A E B | A B.
+        Primary::Optional(seq) if ctx.options.inline_optionals => {
+            let mut defs = convert_definitions_list(seq.into_definitions_list(), ctx).to_vec();
+            push_definitions(&mut defs, vec![BnfDefinition::default()]);
+            defs
+        }
+        primary => vec![convert_primary(primary, ctx).into_iter().collect()],
+    };
+
+    (0..n).fold(vec![BnfDefinition::default()], |defs, _| {
+        concat(defs, &alternatives)
+    })
 }
 
 fn convert_primary(primary: Primary, ctx: &mut Converter) -> Option<BnfTerm> {
     match primary {
         // Convert every option [ E ] to a fresh non-terminal X and add
         // X = $\epsilon$ | E.
-        // (We can convert X = A [ E ] B. to X = A E B | A B.)
         Primary::Optional(seq) => {
-            let symbol = ctx.new_anonymous_rule_name();
+            let defs = seq.into_definitions_list();
 
-            let mut set = convert_definitions_list(seq.into_definitions_list(), ctx);
-            set.insert(0, BnfDefinition::default());
-
-            ctx.push(BnfRule::new(symbol.clone(), set));
-
-            Some(BnfTerm::Symbol(symbol))
+            Some(BnfTerm::Symbol(ctx.anonymous_rule(
+                "opt",
+                defs,
+                |ctx, symbol, defs| {
+                    let mut set = convert_definitions_list(defs, ctx);
+                    set.insert(0, BnfDefinition::default());
+                    BnfRule::new(symbol, set)
+                },
+            )))
         }
         // Convert every repetition { E } to a fresh non-terminal X and add
-        // X = $\epsilon$ | X E.
+        // X = $\epsilon$ | X E, or X = $\epsilon$ | E X if right-recursive.
         Primary::Repeated(seq) => {
-            let symbol = ctx.new_anonymous_rule_name();
+            let defs = seq.into_definitions_list();
 
-            let mut set = convert_definitions_list(seq.into_definitions_list(), ctx);
+            Some(BnfTerm::Symbol(ctx.anonymous_rule(
+                "rep",
+                defs,
+                |ctx, symbol, defs| {
+                    let mut set = convert_definitions_list(defs, ctx);
 
-            set.iter_mut()
-                .for_each(|def| def.insert(0, BnfTerm::Symbol(symbol.clone())));
+                    set.iter_mut().for_each(|def| match ctx.options.repetition {
+                        Recursion::Left => def.insert(0, BnfTerm::Symbol(symbol.clone())),
+                        Recursion::Right => def.push(BnfTerm::Symbol(symbol.clone())),
+                    });
+                    set.insert(0, BnfDefinition::default());
 
-            ctx.push(BnfRule::new(symbol.clone(), set));
-
-            Some(BnfTerm::Symbol(symbol))
+                    BnfRule::new(symbol, set)
+                },
+            )))
         }
         Primary::Grouped(seq) => {
-            let symbol = ctx.new_anonymous_rule_name();
-            let set = convert_definitions_list(seq.into_definitions_list(), ctx);
-            ctx.push(BnfRule::new(symbol.clone(), set));
-            Some(BnfTerm::Symbol(symbol))
+            let defs = seq.into_definitions_list();
+
+            Some(BnfTerm::Symbol(ctx.anonymous_rule(
+                "grp",
+                defs,
+                |ctx, symbol, defs| BnfRule::new(symbol, convert_definitions_list(defs, ctx)),
+            )))
         }
         Primary::MetaIdentifier(meta) => Some(BnfTerm::Symbol(meta.into_symbol())),
         Primary::Literal(lit) => Some(BnfTerm::Literal(BnfLiteral::from(lit.into_symbol()))),
+        // Special sequences are opaque, they are left to the implementation as
+        // an undefined symbol.
+        Primary::Special(seq) => Some(BnfTerm::Symbol(Symbol::from(seq.to_string()))),
         Primary::Empty => None,
     }
 }
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+
+use crate::{prelude::*, primary::PrimaryKind, visit::walk_symbols};
+
+/// Symbols of a syntax, sorted by their role.
+///
+/// Every list is deduplicated and keeps the order of first appearance.
+#[derive(Debug, Default)]
+pub(crate) struct Classification<'s> {
+    /// Meta identifiers defined by a rule.
+    pub non_terminals: Vec<&'s str>,
+    /// Literals found in any definition.
+    pub literals: Vec<&'s str>,
+    /// Meta identifiers referenced in a definition but never defined.
+    pub undefined: Vec<&'s str>,
+}
+
+impl<'s> Classification<'s> {
+    pub fn new<R: IRule>(rules: &'s [R]) -> Self {
+        let mut literals = Vec::<&'s str>::default();
+        let mut meta_identifiers = Vec::<&'s str>::default();
+
+        for rule in rules {
+            walk_symbols(rule.rhs(), &mut |kind, symbol| match kind {
+                PrimaryKind::MetaId => meta_identifiers.push(symbol),
+                PrimaryKind::Literal => literals.push(symbol),
+                _ => {}
+            });
+        }
+
+        let defined = rules
+            .iter()
+            .map(|rule| &**rule.lhs())
+            .collect::<HashSet<_>>();
+
+        let non_terminals = dedup(rules.iter().map(|rule| &**rule.lhs()));
+        let literals = dedup(literals.into_iter());
+        let undefined = dedup(
+            meta_identifiers
+                .into_iter()
+                .filter(|id| !defined.contains(id)),
+        );
+
+        Self {
+            non_terminals,
+            literals,
+            undefined,
+        }
+    }
+
+    /// Terminals are literals, and meta identifiers without a rule which are
+    /// expected to be provided by the lexer (eg. <Integer>).
+    pub fn terminals(&self) -> impl Iterator<Item = &'s str> + '_ {
+        let literals = self.literals.iter().copied();
+        let undefined = self
+            .undefined
+            .iter()
+            .copied()
+            .filter(|id| !self.literals.contains(id));
+
+        literals.chain(undefined)
+    }
+}
+
+fn dedup<'s>(iter: impl Iterator<Item = &'s str>) -> Vec<&'s str> {
+    let mut seen = HashSet::<&'s str>::default();
+    iter.filter(|sym| seen.insert(sym)).collect()
+}
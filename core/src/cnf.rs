@@ -0,0 +1,292 @@
+//! Chomsky Normal Form of a BNF syntax, and a CYK recogniser working on it.
+//!
+//! Every rule of a syntax in CNF derives either two non-terminals, or a
+//! single terminal. Only the start rule, the first one, may derive the empty
+//! string, and it is then never used on a rhs.
+//!
+//! Terminals are the literals, and the symbols which are not defined by a
+//! rule, eg. the special sequences of the EBNF syntax the BNF one has been
+//! lowered from. They are named after their text, as in [crate::analysis].
+use std::collections::HashMap;
+
+use pb_bnf::definition::Definition as BnfDefinition;
+use pb_bnf::literal::Literal as BnfLiteral;
+use pb_bnf::rule::Rule as BnfRule;
+use pb_bnf::symbol::Symbol;
+use pb_bnf::term::Term as BnfTerm;
+
+use crate::{
+    bnf::BnfSyntax,
+    normal_form::{NormalGrammar, Sym},
+};
+
+/// Conversion of a BNF syntax to Chomsky Normal Form.
+pub trait ToCnf {
+    /// Returns an equivalent syntax in CNF, the start rule being the first
+    /// one.
+    ///
+    /// The conversion adds a new start rule if the start symbol is used on a
+    /// rhs, lifts the terminals of long definitions into their own rules,
+    /// binarises the definitions, removes the empty and unit definitions,
+    /// and finally the rules which are unproductive or unreachable.
+    fn to_cnf(&self) -> BnfSyntax;
+}
+
+impl ToCnf for BnfSyntax {
+    fn to_cnf(&self) -> BnfSyntax {
+        let mut grammar = from_bnf(self);
+
+        if grammar.rules.is_empty() {
+            return BnfSyntax::default();
+        }
+
+        grammar.isolate_start();
+        grammar.lift_terminals();
+        grammar.binarise();
+        grammar.remove_empty();
+        grammar.remove_units();
+        grammar.remove_useless();
+        grammar.into_bnf()
+    }
+}
+
+/// Returns true if the syntax is in Chomsky Normal Form.
+pub fn is_cnf(syntax: &BnfSyntax) -> bool {
+    let grammar = from_bnf(syntax);
+
+    if grammar.rules.is_empty() {
+        return true;
+    }
+
+    let start_used = grammar.uses(0);
+
+    grammar.rules.iter().enumerate().all(|(index, rule)| {
+        rule.defs.iter().all(|def| match def.as_slice() {
+            [] => index == 0 && !start_used,
+            [Sym::Terminal(_)] => true,
+            [Sym::Rule(_), Sym::Rule(_)] => true,
+            _ => false,
+        })
+    })
+}
+
+/// Returns true if the syntax, in Chomsky Normal Form, derives the stream of
+/// terminals from its first rule.
+///
+/// Definitions which are not in CNF are ignored, see [ToCnf::to_cnf] to
+/// convert the syntax first.
+pub fn cyk<I>(syntax: &BnfSyntax, tokens: I) -> bool
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let grammar = from_bnf(syntax);
+    let tokens = tokens
+        .into_iter()
+        .map(|token| token.as_ref().to_string())
+        .collect::<Vec<_>>();
+
+    if grammar.rules.is_empty() {
+        return false;
+    }
+
+    if tokens.is_empty() {
+        return grammar.rules[0].defs.iter().any(Vec::is_empty);
+    }
+
+    let mut units = HashMap::<&str, Vec<usize>>::default();
+    let mut pairs = Vec::<(usize, usize, usize)>::default();
+
+    for (lhs, rule) in grammar.rules.iter().enumerate() {
+        for def in rule.defs.iter() {
+            match def.as_slice() {
+                [Sym::Terminal(terminal)] => {
+                    units.entry(terminal.text()).or_default().push(lhs);
+                }
+                [Sym::Rule(left), Sym::Rule(right)] => {
+                    pairs.push((lhs, *left, *right));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let n = tokens.len();
+    let rules = grammar.rules.len();
+    // table[len - 1][start][rule] is true if the rule derives the tokens
+    // start..start + len.
+    let mut table = vec![vec![vec![false; rules]; n]; n];
+
+    for (i, token) in tokens.iter().enumerate() {
+        for &lhs in units.get(token.as_str()).into_iter().flatten() {
+            table[0][i][lhs] = true;
+        }
+    }
+
+    for len in 2..=n {
+        for i in 0..=n - len {
+            for split in 1..len {
+                for &(lhs, left, right) in pairs.iter() {
+                    if table[split - 1][i][left] && table[len - split - 1][i + split][right] {
+                        table[len - 1][i][lhs] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    table[n - 1][0][0]
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Terminal {
+    Literal(String),
+    /// A symbol which is not defined by a rule.
+    Symbol(String),
+}
+
+impl Terminal {
+    fn text(&self) -> &str {
+        match self {
+            Terminal::Literal(text) | Terminal::Symbol(text) => text,
+        }
+    }
+}
+
+type Grammar = NormalGrammar<Terminal>;
+
+/// Merges the definitions of a name, the symbols which are not defined by a
+/// rule being terminals.
+fn from_bnf(syntax: &BnfSyntax) -> Grammar {
+    let mut grammar = Grammar::default();
+    let mut index = HashMap::<String, usize>::default();
+
+    for rule in syntax.iter() {
+        if !index.contains_key(&*rule.lhs) {
+            let id = grammar.add_rule(rule.lhs.to_string(), Some(rule.lhs.to_string()));
+            index.insert(rule.lhs.to_string(), id);
+        }
+    }
+
+    for rule in syntax.iter() {
+        for def in rule.rhs.iter() {
+            let def = def
+                .iter()
+                .map(|term| match term {
+                    BnfTerm::Symbol(symbol) => match index.get(&**symbol) {
+                        Some(&id) => Sym::Rule(id),
+                        None => Sym::Terminal(Terminal::Symbol(symbol.to_string())),
+                    },
+                    BnfTerm::Literal(lit) => Sym::Terminal(Terminal::Literal(lit.to_string())),
+                })
+                .collect();
+
+            grammar.add(index[&*rule.lhs], def);
+        }
+    }
+
+    grammar
+}
+
+impl Grammar {
+    fn into_bnf(self) -> BnfSyntax {
+        let names = self
+            .rules
+            .iter()
+            .map(|rule| rule.name.clone())
+            .collect::<Vec<_>>();
+
+        self.rules
+            .into_iter()
+            .map(|rule| {
+                let rhs = rule
+                    .defs
+                    .into_iter()
+                    .map(|def| {
+                        def.into_iter()
+                            .map(|symbol| match symbol {
+                                Sym::Rule(id) => BnfTerm::Symbol(Symbol::from(names[id].clone())),
+                                Sym::Terminal(Terminal::Symbol(name)) => {
+                                    BnfTerm::Symbol(Symbol::from(name))
+                                }
+                                Sym::Terminal(Terminal::Literal(text)) => {
+                                    BnfTerm::Literal(BnfLiteral::from(Symbol::from(text)))
+                                }
+                            })
+                            .collect::<BnfDefinition>()
+                    })
+                    .collect();
+
+                BnfRule::new(Symbol::from(rule.name), rhs)
+            })
+            .collect()
+    }
+
+    /// Replaces the terminals of the definitions of two symbols or more by
+    /// a rule deriving them.
+    fn lift_terminals(&mut self) {
+        let mut lifted = HashMap::<Terminal, usize>::default();
+
+        for index in 0..self.rules.len() {
+            let mut defs = std::mem::take(&mut self.rules[index].defs);
+
+            for def in defs.iter_mut().filter(|def| def.len() > 1) {
+                for symbol in def.iter_mut() {
+                    let Sym::Terminal(terminal) = symbol else {
+                        continue;
+                    };
+
+                    let rule = match lifted.get(terminal) {
+                        Some(&rule) => rule,
+                        None => {
+                            let name = self.fresh(&format!("T_{}", terminal.text()));
+                            let rule = self.add_rule(name, None);
+                            self.add(rule, vec![Sym::Terminal(terminal.clone())]);
+                            lifted.insert(terminal.clone(), rule);
+                            rule
+                        }
+                    };
+
+                    *symbol = Sym::Rule(rule);
+                }
+            }
+
+            self.rules[index].defs = defs;
+        }
+    }
+
+    /// Splits the definitions of three symbols or more into chains of
+    /// definitions of two symbols.
+    fn binarise(&mut self) {
+        for index in 0..self.rules.len() {
+            let lhs = self.rules[index].name.clone();
+            let origin = self.rules[index].origin.clone();
+            let mut defs = std::mem::take(&mut self.rules[index].defs);
+
+            // A = X1 X2 X3 X4 becomes A = X1 A1; A1 = X2 A2; A2 = X3 X4;
+            for def in defs.iter_mut().filter(|def| def.len() > 2) {
+                let symbols = std::mem::take(def);
+                let n = symbols.len();
+                let rules = (0..n - 2)
+                    .map(|_| {
+                        let name = self.fresh(&format!("{lhs}_bin"));
+                        self.add_rule(name, origin.clone())
+                    })
+                    .collect::<Vec<_>>();
+
+                *def = vec![symbols[0].clone(), Sym::Rule(rules[0])];
+
+                for (k, &rule) in rules.iter().enumerate() {
+                    let rhs = match rules.get(k + 1) {
+                        Some(&next) => vec![symbols[k + 1].clone(), Sym::Rule(next)],
+                        None => vec![symbols[n - 2].clone(), symbols[n - 1].clone()],
+                    };
+
+                    self.add(rule, rhs);
+                }
+            }
+
+            self.rules[index].defs = defs;
+        }
+    }
+}
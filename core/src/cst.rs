@@ -0,0 +1,71 @@
+//! Concrete syntax trees returned by the parsers.
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    /// A rule, its children being the nodes matched by its definitions list.
+    Rule(String),
+    /// A literal, eg. "GOTO".
+    Literal(String),
+    /// A meta identifier which is not defined by a rule, or a special
+    /// sequence, matched from outside the syntax.
+    Terminal(String),
+}
+
+/// A node of a concrete syntax tree.
+///
+/// Optional, repeated and grouped sequences do not get their own nodes, what
+/// they match is added to the children of the enclosing rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub kind: NodeKind,
+    /// Byte range of the input matched by the node.
+    pub span: Range<usize>,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    pub fn rule<S: ToString>(name: S, span: Range<usize>, children: Vec<Node>) -> Self {
+        Self {
+            kind: NodeKind::Rule(name.to_string()),
+            span,
+            children,
+        }
+    }
+
+    pub fn literal<S: ToString>(value: S, span: Range<usize>) -> Self {
+        Self {
+            kind: NodeKind::Literal(value.to_string()),
+            span,
+            children: Vec::default(),
+        }
+    }
+
+    pub fn terminal<S: ToString>(name: S, span: Range<usize>) -> Self {
+        Self {
+            kind: NodeKind::Terminal(name.to_string()),
+            span,
+            children: Vec::default(),
+        }
+    }
+
+    /// Returns the name of the rule, if the node is one.
+    pub fn rule_name(&self) -> Option<&str> {
+        match &self.kind {
+            NodeKind::Rule(name) => Some(name),
+            NodeKind::Literal(_) | NodeKind::Terminal(_) => None,
+        }
+    }
+
+    /// Returns the text matched by the node.
+    pub fn text<'i>(&self, input: &'i str) -> &'i str {
+        &input[self.span.clone()]
+    }
+
+    /// Iterates over the child rules with the given name.
+    pub fn iter_rules<'n>(&'n self, name: &'n str) -> impl Iterator<Item = &'n Node> {
+        self.children
+            .iter()
+            .filter(move |child| child.rule_name() == Some(name))
+    }
+}
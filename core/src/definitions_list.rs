@@ -42,7 +42,7 @@ impl<'a> AsRef<[SingleDefinitionRef<'a>]> for DefinitionsListRef<'a> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct DefinitionsList(Vec<SingleDefinition>);
 
 impl<'a> IterSymbols<'a> for DefinitionsList {
@@ -102,6 +102,14 @@ impl IDefinitionsList for DefinitionsList {
     }
 }
 
+impl std::str::FromStr for DefinitionsList {
+    type Err = crate::parser::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parser::parse_definitions_list(s)
+    }
+}
+
 impl syn::parse::Parse for DefinitionsList {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         use syn::parse::discouraged::Speculative;
@@ -0,0 +1,855 @@
+//! Earley parser, accepting any syntax including ambiguous and left-recursive
+//! ones.
+//!
+//! The syntax is first compiled into plain productions, see [Grammar].
+//! Nullable non-terminals are handled as described by Aycock and Horspool.
+//!
+//! Every derivation of the input is kept in a shared packed parse forest,
+//! see [Forest].
+//!
+//! Completed items only look at the items of their origin set waiting for
+//! their non-terminal. Leo's optimisation is not applied, as the forest is
+//! built from the intermediate items it skips: right-recursive rules take
+//! quadratic time.
+//!
+//! An exception is recognised along the input, from where the production it
+//! excepts starts, rather than in a chart of its own for each start.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ops::Range,
+};
+
+use crate::{
+    cst::Node,
+    grammar::{Grammar, Symbol},
+    interpreter::Matcher,
+    parser::ParseError,
+    prelude::*,
+    syntax::to_syntax,
+};
+
+/// An Earley item, the production matched up to the dot from the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Item {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+/// Earley sets, indexed by byte position.
+#[derive(Debug)]
+struct Chart {
+    sets: Vec<Vec<Item>>,
+    seen: Vec<HashSet<Item>>,
+    /// Items of each set whose dot is before a non-terminal, by non-terminal.
+    waiting: Vec<HashMap<usize, Vec<Item>>>,
+    /// Positions at which the symbol before the dot of an item of each set
+    /// starts, for the items advanced over a symbol.
+    links: Vec<HashMap<Item, BTreeSet<usize>>>,
+    /// Completed items of each set whose exception is not checked yet, along
+    /// with the position at which their last symbol starts.
+    deferred: Vec<Vec<(Item, Option<usize>)>>,
+}
+
+impl Chart {
+    fn new(len: usize) -> Self {
+        Self {
+            sets: vec![Vec::default(); len + 1],
+            seen: vec![HashSet::default(); len + 1],
+            waiting: vec![HashMap::default(); len + 1],
+            links: vec![HashMap::default(); len + 1],
+            deferred: vec![Vec::default(); len + 1],
+        }
+    }
+
+    fn contains(&self, pos: usize, item: Item) -> bool {
+        self.seen[pos].contains(&item)
+    }
+
+    fn insert(&mut self, grammar: &Grammar, pos: usize, item: Item) {
+        if self.seen[pos].insert(item) {
+            self.sets[pos].push(item);
+
+            if let Some(Symbol::NonTerminal(id)) =
+                grammar.productions[item.production].rhs.get(item.dot)
+            {
+                self.waiting[pos].entry(*id).or_default().push(item);
+            }
+        }
+    }
+
+    fn link(&mut self, pos: usize, item: Item, mid: usize) {
+        self.links[pos].entry(item).or_default().insert(mid);
+    }
+
+    /// Returns the positions at which the symbol before the dot of the item
+    /// starts.
+    fn mids(&self, pos: usize, item: Item) -> impl Iterator<Item = usize> + '_ {
+        self.links[pos].get(&item).into_iter().flatten().copied()
+    }
+
+    /// Returns the j-th item of the set waiting for the non-terminal.
+    fn waiting(&self, pos: usize, id: usize, j: usize) -> Option<Item> {
+        self.waiting[pos].get(&id)?.get(j).copied()
+    }
+}
+
+/// Parses text with the Earley algorithm.
+///
+/// Literals are matched as is. Meta identifiers which are not defined by a
+/// rule, and special sequences, are matched with the [Matcher] registered
+/// under their name.
+pub struct EarleyParser {
+    grammar: Grammar,
+    /// Bound on the levels of nested exceptions, the number of productions
+    /// with an exception.
+    exception_depth: usize,
+    terminals: HashMap<String, Matcher>,
+    skip_whitespace: bool,
+}
+
+impl EarleyParser {
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let grammar = Grammar::compile(to_syntax(syntax));
+        let exception_depth = grammar
+            .productions
+            .iter()
+            .filter(|production| production.exception.is_some())
+            .count();
+
+        Self {
+            grammar,
+            exception_depth,
+            terminals: HashMap::default(),
+            skip_whitespace: false,
+        }
+    }
+
+    /// Registers the matcher of a terminal, either the name of an undefined
+    /// meta identifier or the text of a special sequence.
+    pub fn terminal<S, F>(mut self, name: S, matcher: F) -> Self
+    where
+        S: ToString,
+        F: Fn(&str) -> Option<usize> + 'static,
+    {
+        self.terminals.insert(name.to_string(), Box::new(matcher));
+        self
+    }
+
+    /// Skips whitespaces before each terminal and at the end of the input.
+    pub fn skip_whitespace(mut self, skip: bool) -> Self {
+        self.skip_whitespace = skip;
+        self
+    }
+
+    /// Returns true if the input derives from the first rule of the syntax.
+    ///
+    /// Only the chart is filled, the forest is not built.
+    pub fn recognise(&self, input: &str) -> bool {
+        self.start()
+            .is_some_and(|rule| self.recognise_rule(rule, input))
+    }
+
+    /// Returns true if the input derives from the given rule.
+    pub fn recognise_rule(&self, rule: &str, input: &str) -> bool {
+        self.accept(rule, input).is_ok()
+    }
+
+    /// Parses the whole input, starting from the first rule of the syntax.
+    pub fn parse(&self, input: &str) -> Result<Forest, ParseError> {
+        match self.start() {
+            Some(rule) => self.parse_rule(rule, input),
+            None => Err(ParseError::at(input, 0, "the syntax is empty")),
+        }
+    }
+
+    /// Parses the whole input, starting from the given rule.
+    pub fn parse_rule(&self, rule: &str, input: &str) -> Result<Forest, ParseError> {
+        let Acceptance {
+            run,
+            chart,
+            start,
+            end,
+        } = self.accept(rule, input)?;
+
+        Ok(Builder::new(&run, &chart).build(start, end))
+    }
+
+    fn start(&self) -> Option<&str> {
+        self.grammar
+            .non_terminals
+            .first()
+            .and_then(|nt| nt.name.as_deref())
+    }
+
+    /// Fills the chart of the input, and finds the end of the match of the
+    /// rule before the trailing whitespaces.
+    fn accept<'p>(&'p self, rule: &str, input: &'p str) -> Result<Acceptance<'p>, ParseError> {
+        let Some(&start) = self.grammar.names.get(rule) else {
+            return Err(ParseError::at(input, 0, format!("<{rule}> is not defined")));
+        };
+
+        let run = Run {
+            parser: self,
+            input,
+        };
+
+        let chart = run.recognise(start);
+
+        let end = (0..=input.len())
+            .filter(|&end| run.skip(end) == input.len())
+            .find(|&end| run.is_complete(&chart, start, 0, end));
+
+        match end {
+            Some(end) => Ok(Acceptance {
+                run,
+                chart,
+                start,
+                end,
+            }),
+            None => Err(run.error(&chart)),
+        }
+    }
+}
+
+/// A chart in which the start non-terminal derives the input up to end.
+struct Acceptance<'p> {
+    run: Run<'p>,
+    chart: Chart,
+    start: usize,
+    end: usize,
+}
+
+struct Run<'p> {
+    parser: &'p EarleyParser,
+    input: &'p str,
+}
+
+impl Run<'_> {
+    fn grammar(&self) -> &Grammar {
+        &self.parser.grammar
+    }
+
+    fn skip(&self, pos: usize) -> usize {
+        if self.parser.skip_whitespace {
+            let rest = &self.input[pos..];
+            pos + rest.len() - rest.trim_start().len()
+        } else {
+            pos
+        }
+    }
+
+    /// Returns the end of the terminal if it matches at the position.
+    fn scan(&self, symbol: &Symbol, pos: usize) -> Option<usize> {
+        let pos = self.skip(pos);
+        let rest = &self.input[pos..];
+
+        let len = match symbol {
+            Symbol::Literal(lit) => rest.starts_with(lit.as_str()).then_some(lit.len()),
+            Symbol::Terminal(name) => self
+                .parser
+                .terminals
+                .get(name)
+                .and_then(|matcher| matcher(rest)),
+            Symbol::NonTerminal(_) => None,
+        };
+
+        len.map(|len| pos + len)
+    }
+
+    /// Returns true if the non-terminal derives the input between start and end.
+    fn is_complete(&self, chart: &Chart, id: usize, start: usize, end: usize) -> bool {
+        self.grammar().non_terminals[id]
+            .productions
+            .iter()
+            .any(|&production| {
+                let dot = self.grammar().productions[production].rhs.len();
+
+                chart.contains(
+                    end,
+                    Item {
+                        production,
+                        dot,
+                        origin: start,
+                    },
+                )
+            })
+    }
+
+    /// Adds the item to the chart of a level, the symbol before its dot
+    /// deriving the input between mid and pos. The start of a production
+    /// with an exception predicts the exception in the next level, and its
+    /// completion is deferred until the set is closed, see [Run::resolve].
+    fn insert(
+        &self,
+        charts: &mut Vec<Chart>,
+        level: usize,
+        pos: usize,
+        item: Item,
+        mid: Option<usize>,
+    ) {
+        let production = &self.grammar().productions[item.production];
+        // Past the nesting depth, an exception can only be recursive.
+        let exception = production
+            .exception
+            .filter(|_| level < self.parser.exception_depth);
+
+        let new = if exception.is_some() && item.dot == production.rhs.len() {
+            let deferred = &mut charts[level].deferred[pos];
+            let new = !deferred.contains(&(item, mid));
+
+            if new {
+                deferred.push((item, mid));
+            }
+
+            new
+        } else {
+            self.add(&mut charts[level], pos, item, mid)
+        };
+
+        if let Some(id) = exception.filter(|_| new && item.dot == 0) {
+            if charts.len() == level + 1 {
+                charts.push(Chart::new(self.input.len()));
+            }
+
+            self.predict(charts, level + 1, pos, id);
+        }
+    }
+
+    /// Adds the item to the chart, returns true if it was not there yet.
+    fn add(&self, chart: &mut Chart, pos: usize, item: Item, mid: Option<usize>) -> bool {
+        let new = !chart.contains(pos, item);
+
+        chart.insert(self.grammar(), pos, item);
+
+        if let Some(mid) = mid {
+            chart.link(pos, item, mid);
+        }
+
+        new
+    }
+
+    /// Adds the productions of the non-terminal, starting at the position.
+    fn predict(&self, charts: &mut Vec<Chart>, level: usize, pos: usize, id: usize) {
+        for &production in self.grammar().non_terminals[id].productions.iter() {
+            let item = Item {
+                production,
+                dot: 0,
+                origin: pos,
+            };
+
+            self.insert(charts, level, pos, item, None);
+        }
+    }
+
+    /// Adds the item advanced over the symbol after its dot, which derives
+    /// the input between mid and pos.
+    fn advance(&self, charts: &mut Vec<Chart>, level: usize, mid: usize, pos: usize, item: Item) {
+        self.insert(charts, level, pos, advance(item), Some(mid));
+    }
+
+    /// Adds the deferred completions of the deepest level whose match does
+    /// not derive from their exception, returns false if there were none.
+    ///
+    /// The sets of every level are closed, and the next level has nothing
+    /// deferred, so that its matches of the exceptions are complete.
+    fn resolve(&self, charts: &mut [Chart], pos: usize) -> bool {
+        let Some(level) = (0..charts.len())
+            .rev()
+            .find(|&level| !charts[level].deferred[pos].is_empty())
+        else {
+            return false;
+        };
+
+        let (charts, next) = charts.split_at_mut(level + 1);
+        let chart = &mut charts[level];
+
+        for (item, mid) in std::mem::take(&mut chart.deferred[pos]) {
+            let exception = self.grammar().productions[item.production].exception;
+            let excepted =
+                exception.is_some_and(|id| self.is_complete(&next[0], id, item.origin, pos));
+
+            if !excepted {
+                self.add(chart, pos, item, mid);
+            }
+        }
+
+        true
+    }
+
+    /// Fills the chart of the input from the start non-terminal.
+    ///
+    /// The exceptions are recognised along the input, in a chart per level
+    /// of nesting, from the positions at which the productions they except
+    /// start. The chart of the input only holds the matches of the syntax,
+    /// which the forest and the errors are built from.
+    fn recognise(&self, start: usize) -> Chart {
+        let len = self.input.len();
+        let mut charts = vec![Chart::new(len)];
+
+        self.predict(&mut charts, 0, 0, start);
+
+        for pos in 0..=len {
+            // Items of the set of each level processed so far.
+            let mut processed = Vec::<usize>::default();
+
+            loop {
+                while let Some(level) = (0..charts.len()).find(|&level| {
+                    processed.get(level).copied().unwrap_or_default()
+                        < charts[level].sets[pos].len()
+                }) {
+                    processed.resize(charts.len(), 0);
+                    let item = charts[level].sets[pos][processed[level]];
+                    processed[level] += 1;
+                    self.process(&mut charts, level, pos, item);
+                }
+
+                if !self.resolve(&mut charts, pos) {
+                    break;
+                }
+            }
+        }
+
+        charts.swap_remove(0)
+    }
+
+    fn process(&self, charts: &mut Vec<Chart>, level: usize, pos: usize, item: Item) {
+        let grammar = self.grammar();
+        let production = &grammar.productions[item.production];
+
+        match production.rhs.get(item.dot) {
+            // Completion.
+            None => {
+                let mut j = 0;
+
+                while let Some(waiting) = charts[level].waiting(item.origin, production.lhs, j) {
+                    self.advance(charts, level, item.origin, pos, waiting);
+                    j += 1;
+                }
+            }
+            // Prediction.
+            Some(Symbol::NonTerminal(id)) => {
+                self.predict(charts, level, pos, *id);
+
+                if grammar.nullable[*id] {
+                    self.advance(charts, level, pos, pos, item);
+                }
+            }
+            // Scanning.
+            Some(symbol) => {
+                if let Some(end) = self.scan(symbol, pos) {
+                    self.advance(charts, level, pos, end, item);
+                }
+            }
+        }
+    }
+
+    /// Reports the terminals expected at the furthest position reached.
+    fn error(&self, chart: &Chart) -> ParseError {
+        let pos = (0..chart.sets.len())
+            .rev()
+            .find(|&pos| !chart.sets[pos].is_empty())
+            .unwrap_or_default();
+
+        let mut expected = BTreeSet::<String>::default();
+
+        for item in chart.sets[pos].iter() {
+            match self.grammar().productions[item.production]
+                .rhs
+                .get(item.dot)
+            {
+                Some(Symbol::Literal(lit)) => expected.insert(format!("{lit:?}")),
+                Some(Symbol::Terminal(name)) => expected.insert(format!("<{name}>")),
+                Some(Symbol::NonTerminal(_)) => false,
+                None => expected.insert("end of input".to_string()),
+            };
+        }
+
+        let expected = expected.into_iter().collect::<Vec<_>>();
+
+        ParseError::at(
+            self.input,
+            self.skip(pos),
+            format!("expecting {}", expected.join(" or ")),
+        )
+    }
+}
+
+fn advance(item: Item) -> Item {
+    Item {
+        dot: item.dot + 1,
+        ..item
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Label {
+    Rule(String),
+    /// An optional, repeated or grouped sequence.
+    Sequence,
+    Literal(String),
+    /// A meta identifier which is not defined by a rule, or a special sequence.
+    Terminal(String),
+    /// A prefix of the symbols of a production, shared by its derivations.
+    Intermediate,
+}
+
+/// One derivation of a node: the node of the previous symbols, if any, and
+/// the node of the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Family {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForestNode {
+    pub label: Label,
+    pub span: Range<usize>,
+    /// The derivations of the node, more than one if it is ambiguous.
+    pub families: Vec<Family>,
+}
+
+/// A shared packed parse forest.
+///
+/// Nodes are shared between the derivations they take part in, and a node
+/// with several derivations packs them as several families. Families are
+/// binarised with intermediate nodes, so that the forest stays polynomial
+/// in the size of the input.
+#[derive(Debug, Clone, Default)]
+pub struct Forest {
+    pub(crate) nodes: Vec<ForestNode>,
+    pub(crate) root: usize,
+}
+
+impl Forest {
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    pub fn node(&self, id: usize) -> &ForestNode {
+        &self.nodes[id]
+    }
+
+    pub fn iter_nodes(&self) -> impl Iterator<Item = &ForestNode> {
+        self.nodes.iter()
+    }
+
+    /// Returns true if the input has more than one derivation.
+    pub fn is_ambiguous(&self) -> bool {
+        let mut visited = HashSet::<usize>::default();
+        let mut stack = vec![self.root];
+
+        while let Some(id) = stack.pop() {
+            if !visited.insert(id) {
+                continue;
+            }
+
+            let node = &self.nodes[id];
+
+            if node.families.len() > 1 {
+                return true;
+            }
+
+            for family in node.families.iter() {
+                stack.extend(family.left.into_iter().chain(family.right));
+            }
+        }
+
+        false
+    }
+
+    /// Returns the derivations of the node, as lists of children, the
+    /// intermediate nodes being expanded.
+    pub fn derivations(&self, id: usize) -> Vec<Vec<usize>> {
+        self.nodes[id]
+            .families
+            .iter()
+            .flat_map(|family| {
+                let prefixes = match family.left {
+                    Some(left) => self.derivations(left),
+                    None => vec![Vec::default()],
+                };
+
+                prefixes.into_iter().map(move |mut children| {
+                    children.extend(family.right);
+                    children
+                })
+            })
+            .collect()
+    }
+
+    /// Returns up to limit concrete syntax trees of the input.
+    ///
+    /// Derivations going through a cycle (eg. <a> = <a> | "x";) are skipped,
+    /// as they would be infinite.
+    pub fn trees(&self, limit: usize) -> Vec<Node> {
+        self.expand(self.root, limit, &mut Vec::default())
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Returns the ways to derive the node, each one being a list of
+    /// concrete nodes, more than one for the anonymous sequences.
+    fn expand(&self, id: usize, limit: usize, path: &mut Vec<usize>) -> Vec<Vec<Node>> {
+        if path.contains(&id) {
+            return Vec::default();
+        }
+
+        let node = &self.nodes[id];
+
+        match &node.label {
+            Label::Literal(lit) => return vec![vec![Node::literal(lit, node.span.clone())]],
+            Label::Terminal(name) => return vec![vec![Node::terminal(name, node.span.clone())]],
+            Label::Rule(_) | Label::Sequence | Label::Intermediate => {}
+        }
+
+        path.push(id);
+
+        let mut expansions = Vec::<Vec<Node>>::default();
+
+        for children in self.derivations(id) {
+            let mut products = vec![Vec::<Node>::default()];
+
+            for child in children {
+                let alternatives = self.expand(child, limit, path);
+
+                products = products
+                    .iter()
+                    .flat_map(|prefix| {
+                        alternatives.iter().map(move |nodes| {
+                            let mut product = prefix.clone();
+                            product.extend(nodes.iter().cloned());
+                            product
+                        })
+                    })
+                    .take(limit)
+                    .collect();
+            }
+
+            expansions.extend(products);
+            expansions.truncate(limit);
+        }
+
+        path.pop();
+
+        match &node.label {
+            Label::Rule(name) => expansions
+                .into_iter()
+                .map(|children| vec![Node::rule(name, node.span.clone(), children)])
+                .collect(),
+            _ => expansions,
+        }
+    }
+}
+
+/// Builds the forest from the completed items of the chart.
+///
+/// Nodes are created when first referenced, and their families are computed
+/// from a worklist, so that deep derivations do not grow the call stack.
+struct Builder<'r, 'p> {
+    run: &'r Run<'p>,
+    chart: &'r Chart,
+    forest: Forest,
+    symbols: HashMap<(Symbol, usize, usize), usize>,
+    intermediates: HashMap<(usize, usize, usize, usize), usize>,
+    /// Nodes whose families are still to be computed.
+    pending: Vec<Pending>,
+}
+
+enum Pending {
+    NonTerminal {
+        id: usize,
+        nt: usize,
+        start: usize,
+        end: usize,
+    },
+    Intermediate {
+        id: usize,
+        production: usize,
+        dot: usize,
+        start: usize,
+        end: usize,
+    },
+}
+
+impl<'r, 'p> Builder<'r, 'p> {
+    fn new(run: &'r Run<'p>, chart: &'r Chart) -> Self {
+        Self {
+            run,
+            chart,
+            forest: Forest::default(),
+            symbols: HashMap::default(),
+            intermediates: HashMap::default(),
+            pending: Vec::default(),
+        }
+    }
+
+    fn build(mut self, start: usize, end: usize) -> Forest {
+        self.forest.root = self.symbol(&Symbol::NonTerminal(start), 0, end);
+
+        while let Some(pending) = self.pending.pop() {
+            let (id, families) = match pending {
+                Pending::NonTerminal { id, nt, start, end } => {
+                    (id, self.non_terminal_families(nt, start, end))
+                }
+                Pending::Intermediate {
+                    id,
+                    production,
+                    dot,
+                    start,
+                    end,
+                } => (id, self.prefix(production, dot, start, end)),
+            };
+
+            self.forest.nodes[id].families = families;
+        }
+
+        self.forest
+    }
+
+    fn add(&mut self, label: Label, span: Range<usize>) -> usize {
+        self.forest.nodes.push(ForestNode {
+            label,
+            span,
+            families: Vec::default(),
+        });
+        self.forest.nodes.len() - 1
+    }
+
+    fn symbol(&mut self, symbol: &Symbol, start: usize, end: usize) -> usize {
+        let key = (symbol.clone(), start, end);
+
+        if let Some(&id) = self.symbols.get(&key) {
+            return id;
+        }
+
+        // Skipped whitespaces are not part of the node.
+        let span = self.run.skip(start).min(end)..end;
+
+        let id = match symbol {
+            Symbol::Literal(lit) => self.add(Label::Literal(lit.clone()), span),
+            Symbol::Terminal(name) => self.add(Label::Terminal(name.clone()), span),
+            Symbol::NonTerminal(nt) => {
+                let label = match &self.run.grammar().non_terminals[*nt].name {
+                    Some(name) => Label::Rule(name.clone()),
+                    None => Label::Sequence,
+                };
+
+                let id = self.add(label, span);
+                self.pending.push(Pending::NonTerminal {
+                    id,
+                    nt: *nt,
+                    start,
+                    end,
+                });
+                id
+            }
+        };
+
+        self.symbols.insert(key, id);
+        id
+    }
+
+    fn non_terminal_families(&mut self, nt: usize, start: usize, end: usize) -> Vec<Family> {
+        let productions = self.run.grammar().non_terminals[nt].productions.clone();
+        let mut families = Vec::<Family>::default();
+
+        for production in productions {
+            let dot = self.run.grammar().productions[production].rhs.len();
+            let item = Item {
+                production,
+                dot,
+                origin: start,
+            };
+
+            if self.chart.contains(end, item) {
+                families.extend(self.prefix(production, dot, start, end));
+            }
+        }
+
+        families
+    }
+
+    fn intermediate(&mut self, production: usize, dot: usize, start: usize, end: usize) -> usize {
+        let key = (production, dot, start, end);
+
+        if let Some(&id) = self.intermediates.get(&key) {
+            return id;
+        }
+
+        let id = self.add(Label::Intermediate, start..end);
+        self.intermediates.insert(key, id);
+        self.pending.push(Pending::Intermediate {
+            id,
+            production,
+            dot,
+            start,
+            end,
+        });
+        id
+    }
+
+    /// Returns the ways the first dot symbols of the production derive the
+    /// input between start and end.
+    fn prefix(&mut self, production: usize, dot: usize, start: usize, end: usize) -> Vec<Family> {
+        if dot == 0 {
+            return if start == end {
+                vec![Family {
+                    left: None,
+                    right: None,
+                }]
+            } else {
+                Vec::default()
+            };
+        }
+
+        let symbol = self.run.grammar().productions[production].rhs[dot - 1].clone();
+        let mut families = Vec::<Family>::default();
+
+        let mids = self
+            .chart
+            .mids(
+                end,
+                Item {
+                    production,
+                    dot,
+                    origin: start,
+                },
+            )
+            .collect::<Vec<_>>();
+
+        for mid in mids {
+            let item = Item {
+                production,
+                dot: dot - 1,
+                origin: start,
+            };
+
+            if (dot == 1 && mid != start)
+                || !self.chart.contains(mid, item)
+                || !self.derives(&symbol, mid, end)
+            {
+                continue;
+            }
+
+            let right = self.symbol(&symbol, mid, end);
+            let left = (dot > 1).then(|| self.intermediate(production, dot - 1, start, mid));
+
+            families.push(Family {
+                left,
+                right: Some(right),
+            });
+        }
+
+        families
+    }
+
+    fn derives(&self, symbol: &Symbol, start: usize, end: usize) -> bool {
+        match symbol {
+            Symbol::NonTerminal(id) => self.run.is_complete(self.chart, *id, start, end),
+            symbol => self.run.scan(symbol, start) == Some(end),
+        }
+    }
+}
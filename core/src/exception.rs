@@ -6,7 +6,7 @@ use crate::{
 };
 
 pub trait IException: Deref<Target = Self::Factor> {
-    type Factor;
+    type Factor: IFactor;
 
     fn to_owned(self) -> Exception;
 }
@@ -39,6 +39,12 @@ impl<'a> Deref for ExceptionRef<'a> {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Exception(Factor);
 
+impl Exception {
+    pub fn new(factor: Factor) -> Self {
+        Self(factor)
+    }
+}
+
 impl IException for Exception {
     type Factor = Factor;
 
@@ -0,0 +1,138 @@
+//! Left factoring of alternatives sharing a common prefix.
+//!
+//! <cmd> = "RUN" | "RUN", <expr> | "GET", "ID" | "GET", "#", <expr>;
+//!
+//! becomes
+//!
+//! <cmd> = "RUN", [<expr>] | "GET", ("ID" | "#", <expr>);
+use crate::{
+    prelude::*, single_definition::is_empty, syntax::to_syntax, DefinitionsList, GroupedSequence,
+    OptionalSequence, Primary, RepeatedSequence, Rule, SingleDefinition, Syntax, Term,
+};
+
+/// The result of left factoring a syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeftFactoring {
+    pub syntax: Syntax,
+    /// The lhs of the rules which have been rewritten, in order.
+    pub changed: Vec<String>,
+}
+
+/// Left factors every rule of the syntax.
+pub fn left_factor_syntax<S: ISyntax>(syntax: &S) -> LeftFactoring {
+    let mut changed = Vec::<String>::default();
+
+    let syntax = to_syntax(syntax)
+        .into_iter()
+        .map(|rule| {
+            let rhs = left_factor(&rule.rhs);
+
+            if rhs != rule.rhs {
+                changed.push(rule.lhs.to_string());
+            }
+
+            Rule::new(rule.lhs, rhs)
+        })
+        .collect();
+
+    LeftFactoring { syntax, changed }
+}
+
+/// Factors out the longest common prefix of alternatives starting with the
+/// same term, including in nested sequences.
+///
+/// The remaining suffixes are grouped, or made optional if one of them is
+/// empty. Alternatives keep the order in which their first term appears.
+pub fn left_factor(defs: &DefinitionsList) -> DefinitionsList {
+    let defs = defs
+        .iter()
+        .map(|def| {
+            def.iter()
+                .map(left_factor_term)
+                .collect::<SingleDefinition>()
+        })
+        .collect::<Vec<_>>();
+
+    // Alternatives sharing the same first term, empty ones are never merged.
+    let mut groups = Vec::<Vec<SingleDefinition>>::default();
+
+    for def in defs {
+        let group = groups.iter_mut().find(|group| {
+            !is_empty(&def) && !is_empty(&group[0]) && group[0].first() == def.first()
+        });
+
+        match group {
+            Some(group) => group.push(def),
+            None => groups.push(vec![def]),
+        }
+    }
+
+    groups.into_iter().map(factor_group).collect()
+}
+
+fn left_factor_term(term: &Term) -> Term {
+    let primary = match &term.primary {
+        Primary::Grouped(seq) => Primary::Grouped(GroupedSequence::new(left_factor(seq.as_ref()))),
+        Primary::Optional(seq) => Primary::Optional(OptionalSequence::new(left_factor(seq))),
+        Primary::Repeated(seq) => {
+            Primary::Repeated(RepeatedSequence::new(left_factor(seq.as_ref())))
+        }
+        primary => primary.clone(),
+    };
+
+    let mut term = term.clone();
+    term.factor.primary = primary;
+    term
+}
+
+/// Merges alternatives starting with the same term.
+fn factor_group(mut group: Vec<SingleDefinition>) -> SingleDefinition {
+    if group.len() == 1 {
+        return group.remove(0);
+    }
+
+    let prefix = (1..)
+        .take_while(|&n| {
+            group
+                .iter()
+                .all(|def| def.len() >= n && def[..n] == group[0][..n])
+        })
+        .last()
+        .unwrap_or(1);
+
+    let mut has_empty = false;
+    let mut suffixes = DefinitionsList::default();
+
+    for def in group.iter() {
+        if def.len() == prefix {
+            has_empty = true;
+        } else {
+            let suffix = def[prefix..].iter().cloned().collect::<SingleDefinition>();
+
+            if !suffixes.contains(&suffix) {
+                suffixes.push(suffix);
+            }
+        }
+    }
+
+    let mut factored = group.swap_remove(0)[..prefix]
+        .iter()
+        .cloned()
+        .collect::<SingleDefinition>();
+
+    if !suffixes.is_empty() {
+        let suffixes = left_factor(&suffixes);
+
+        if has_empty {
+            factored.push(Term::from(Primary::Optional(OptionalSequence::new(
+                suffixes,
+            ))));
+        } else if suffixes.len() == 1 {
+            factored.extend(suffixes.into_iter().flatten());
+        } else {
+            factored.push(Term::from(Primary::Grouped(GroupedSequence::new(suffixes))));
+        }
+    }
+
+    factored
+}
@@ -0,0 +1,419 @@
+//! Random sentences of a syntax, eg. to fuzz a parser against its reference
+//! grammar.
+//!
+//! The generator is driven by a small seedable PRNG, so that a failing
+//! sentence can be reproduced from its seed.
+use std::collections::HashMap;
+
+use crate::{
+    earley::EarleyParser, prelude::*, syntax::to_syntax, DefinitionsList, Factor, MetaIdentifier,
+    Primary, Rule, SingleDefinition, Syntax, Term,
+};
+
+/// A xorshift* pseudo-random number generator.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Scrambles the seed, the state must not be zero.
+        let state = (seed ^ 0x9E37_79B9_7F4A_7C15).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        Self(state | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Returns a number in 0..n.
+    ///
+    /// # Panics
+    ///
+    /// Panics if n is 0, the range being empty.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "Rng::below: the range 0..0 is empty");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns true one time out of two.
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+}
+
+/// Generates the text of a terminal.
+pub type TerminalGenerator = Box<dyn Fn(&mut Rng) -> String>;
+
+/// Generates random sentences of a syntax.
+///
+/// Literals are generated as is. Meta identifiers which are not defined by a
+/// rule, and special sequences, are generated with the [TerminalGenerator]
+/// registered under their name, or as their name if there is none.
+pub struct Generator {
+    /// Alternatives of each rule, indexed by name.
+    names: HashMap<String, usize>,
+    rules: Vec<(String, Vec<SingleDefinition>)>,
+    /// Least depth at which each rule can terminate, None if it never does.
+    heights: Vec<Option<usize>>,
+    terminals: HashMap<String, TerminalGenerator>,
+    rng: Rng,
+    max_depth: usize,
+    separator: String,
+    attempts: usize,
+    /// Recognises the excepted factors, by their text.
+    exceptions: Option<(EarleyParser, HashMap<String, String>)>,
+}
+
+impl Generator {
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let syntax = to_syntax(syntax);
+        let mut names = HashMap::<String, usize>::default();
+        let mut rules = Vec::<(String, Vec<SingleDefinition>)>::default();
+
+        for rule in syntax.iter() {
+            let index = *names.entry(rule.lhs.to_string()).or_insert_with(|| {
+                rules.push((rule.lhs.to_string(), Vec::default()));
+                rules.len() - 1
+            });
+
+            rules[index].1.extend(rule.rhs.iter().cloned());
+        }
+
+        let mut generator = Self {
+            names,
+            rules,
+            heights: Vec::default(),
+            terminals: HashMap::default(),
+            rng: Rng::new(0),
+            max_depth: 32,
+            separator: String::default(),
+            attempts: 16,
+            exceptions: None,
+        };
+
+        generator.compute_heights();
+        generator.exceptions = generator.compile_exceptions(&syntax);
+        generator
+    }
+
+    /// Registers the generator of a terminal, either the name of an
+    /// undefined meta identifier or the text of a special sequence.
+    pub fn terminal<S, F>(mut self, name: S, generator: F) -> Self
+    where
+        S: ToString,
+        F: Fn(&mut Rng) -> String + 'static,
+    {
+        self.terminals.insert(name.to_string(), Box::new(generator));
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Caps the nesting of rules, 32 by default.
+    ///
+    /// Close to the cap, only the alternatives which terminate the soonest
+    /// are chosen, and sequences are no longer repeated.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Inserts a separator between the terminals, eg. a space for syntaxes
+    /// whose terminals are tokens.
+    pub fn separator<S: ToString>(mut self, separator: S) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Number of times a sentence is generated again when an exception
+    /// rejects it, 16 by default.
+    pub fn attempts(mut self, attempts: usize) -> Self {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Generates a sentence of the first rule of the syntax.
+    pub fn generate(&mut self) -> Option<String> {
+        let rule = self.rules.first()?.0.clone();
+        self.generate_rule(&rule)
+    }
+
+    /// Generates a sentence of the rule, None if the rule is not defined,
+    /// cannot terminate within the maximum depth, or if every attempt has
+    /// been rejected by an exception.
+    pub fn generate_rule(&mut self, rule: &str) -> Option<String> {
+        let index = *self.names.get(rule)?;
+
+        for _ in 0..self.attempts.max(1) {
+            let mut tokens = Vec::<String>::default();
+
+            if self.rule(index, 0, &mut tokens) {
+                return Some(tokens.join(&self.separator));
+            }
+        }
+
+        None
+    }
+
+    fn compute_heights(&mut self) {
+        self.heights = vec![None; self.rules.len()];
+
+        loop {
+            let mut changed = false;
+
+            for index in 0..self.rules.len() {
+                let height = self.rules[index]
+                    .1
+                    .iter()
+                    .filter_map(|def| self.height_of_definition(def))
+                    .min()
+                    .map(|height| height + 1);
+
+                if height.is_some() && height < self.heights[index].or(Some(usize::MAX)) {
+                    self.heights[index] = height;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    fn height_of_definition(&self, def: &SingleDefinition) -> Option<usize> {
+        def.iter()
+            .map(|term| self.height_of_primary(&term.primary))
+            .try_fold(0, |max, height| Some(max.max(height?)))
+    }
+
+    fn height_of_definitions_list(&self, defs: &DefinitionsList) -> Option<usize> {
+        defs.iter()
+            .filter_map(|def| self.height_of_definition(def))
+            .min()
+    }
+
+    fn height_of_primary(&self, primary: &Primary) -> Option<usize> {
+        match primary {
+            Primary::Optional(_) | Primary::Repeated(_) => Some(0),
+            Primary::Grouped(seq) => self.height_of_definitions_list(seq.as_ref()),
+            Primary::MetaIdentifier(id) => match self.names.get(&**id) {
+                Some(&index) => self.heights[index],
+                None => Some(0),
+            },
+            Primary::Literal(_) | Primary::Special(_) | Primary::Empty => Some(0),
+        }
+    }
+
+    /// Builds a parser recognising the excepted factors, each one being
+    /// added as a rule.
+    fn compile_exceptions(
+        &self,
+        syntax: &Syntax,
+    ) -> Option<(EarleyParser, HashMap<String, String>)> {
+        let mut factors = Vec::<Factor>::default();
+
+        for (_, defs) in self.rules.iter() {
+            for def in defs.iter() {
+                collect_exceptions(def, &mut factors);
+            }
+        }
+
+        if factors.is_empty() {
+            return None;
+        }
+
+        let mut augmented = syntax.clone();
+        let mut rules = HashMap::<String, String>::default();
+
+        for factor in factors {
+            let text = factor.to_string();
+
+            if rules.contains_key(&text) {
+                continue;
+            }
+
+            let name = (0..)
+                .map(|i| format!("exception_{i}"))
+                .find(|name| !self.names.contains_key(name) && !rules.values().any(|r| r == name))
+                .unwrap();
+
+            augmented.push(Rule::new(
+                MetaIdentifier::new(&name),
+                DefinitionsList::from_iter([SingleDefinition::from_iter([Term::from(factor)])]),
+            ));
+            rules.insert(text, name);
+        }
+
+        let skip = !self.separator.is_empty() && self.separator.trim().is_empty();
+        let parser = EarleyParser::new(&augmented).skip_whitespace(skip);
+        Some((parser, rules))
+    }
+
+    /// Returns true if the tokens match the excepted factor.
+    fn is_excepted(&self, exception: &Factor, tokens: &[String]) -> bool {
+        let Some((parser, rules)) = &self.exceptions else {
+            return false;
+        };
+
+        let text = tokens.join(&self.separator);
+        let rule = &rules[&exception.to_string()];
+        parser.recognise_rule(rule, &text)
+    }
+
+    /// Returns the alternatives which can terminate in the remaining depth,
+    /// only the ones terminating the soonest when close to the limit.
+    fn candidates(&self, defs: &[SingleDefinition], remaining: usize) -> Vec<usize> {
+        let heights = defs
+            .iter()
+            .map(|def| self.height_of_definition(def))
+            .collect::<Vec<_>>();
+
+        let Some(least) = heights.iter().flatten().min().copied() else {
+            return Vec::default();
+        };
+
+        let limit = if remaining <= least + 1 {
+            least
+        } else {
+            remaining.max(least)
+        };
+
+        (0..defs.len())
+            .filter(|&i| heights[i].is_some_and(|height| height <= limit))
+            .collect()
+    }
+
+    fn rule(&mut self, index: usize, depth: usize, tokens: &mut Vec<String>) -> bool {
+        let remaining = self.max_depth.saturating_sub(depth + 1);
+        let candidates = self.candidates(&self.rules[index].1, remaining);
+
+        if candidates.is_empty() {
+            return false;
+        }
+
+        let def = self.rules[index].1[candidates[self.rng.below(candidates.len())]].clone();
+        self.single_definition(&def, depth + 1, tokens)
+    }
+
+    fn definitions_list(
+        &mut self,
+        defs: &DefinitionsList,
+        depth: usize,
+        tokens: &mut Vec<String>,
+    ) -> bool {
+        let remaining = self.max_depth.saturating_sub(depth);
+        let candidates = self.candidates(defs, remaining);
+
+        if candidates.is_empty() {
+            return false;
+        }
+
+        let def = &defs[candidates[self.rng.below(candidates.len())]];
+        self.single_definition(def, depth, tokens)
+    }
+
+    fn single_definition(
+        &mut self,
+        def: &SingleDefinition,
+        depth: usize,
+        tokens: &mut Vec<String>,
+    ) -> bool {
+        def.iter().all(|term| self.term(term, depth, tokens))
+    }
+
+    /// Generates the factor again until it does not match the exception.
+    fn term(&mut self, term: &Term, depth: usize, tokens: &mut Vec<String>) -> bool {
+        let Some(exception) = &term.exception else {
+            return self.factor(&term.factor, depth, tokens);
+        };
+
+        for _ in 0..self.attempts.max(1) {
+            let mut candidate = Vec::<String>::default();
+
+            if self.factor(&term.factor, depth, &mut candidate)
+                && !self.is_excepted(exception, &candidate)
+            {
+                tokens.extend(candidate);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn factor(&mut self, factor: &Factor, depth: usize, tokens: &mut Vec<String>) -> bool {
+        (0..factor.repetition.unwrap_or(1)).all(|_| self.primary(&factor.primary, depth, tokens))
+    }
+
+    fn primary(&mut self, primary: &Primary, depth: usize, tokens: &mut Vec<String>) -> bool {
+        let remaining = self.max_depth.saturating_sub(depth);
+
+        match primary {
+            Primary::Optional(seq) => {
+                let fits = self
+                    .height_of_definitions_list(seq)
+                    .is_some_and(|height| height + 1 < remaining);
+
+                !(fits && self.rng.coin()) || self.definitions_list(seq, depth, tokens)
+            }
+            Primary::Repeated(seq) => {
+                let fits = self
+                    .height_of_definitions_list(seq.as_ref())
+                    .is_some_and(|height| height + 1 < remaining);
+
+                while fits && self.rng.coin() {
+                    if !self.definitions_list(seq.as_ref(), depth, tokens) {
+                        return false;
+                    }
+                }
+
+                true
+            }
+            Primary::Grouped(seq) => self.definitions_list(seq.as_ref(), depth, tokens),
+            Primary::MetaIdentifier(id) => match self.names.get(&**id) {
+                Some(&index) => self.rule(index, depth, tokens),
+                None => {
+                    tokens.push(self.terminal_text(id));
+                    true
+                }
+            },
+            Primary::Literal(lit) => {
+                tokens.push(lit.to_string());
+                true
+            }
+            Primary::Special(seq) => {
+                tokens.push(self.terminal_text(seq));
+                true
+            }
+            Primary::Empty => true,
+        }
+    }
+
+    fn terminal_text(&mut self, name: &str) -> String {
+        match self.terminals.get(name) {
+            Some(generator) => generator(&mut self.rng),
+            None => name.to_string(),
+        }
+    }
+}
+
+fn collect_exceptions(def: &SingleDefinition, factors: &mut Vec<Factor>) {
+    for term in def.iter() {
+        if let Some(exception) = &term.exception {
+            factors.push(Factor::clone(exception));
+        }
+
+        if let Some(defs) = term.primary.try_as_definitions_list() {
+            for def in defs.iter() {
+                collect_exceptions(def, factors);
+            }
+        }
+    }
+}
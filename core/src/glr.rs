@@ -0,0 +1,366 @@
+//! Generalised LR parser, for syntaxes whose LALR(1) automaton has
+//! conflicts.
+//!
+//! The parser forks on every conflicting cell of the ACTION table. The
+//! stacks of the forks are merged into a graph-structured stack, whose nodes
+//! are the states reached at each position, and every parse of the input is
+//! kept in a shared packed parse [Forest].
+//!
+//! As with [crate::lr], the parser reads a stream of terminals, and the
+//! spans of the forest nodes are ranges of token indices.
+use std::collections::HashMap;
+
+use crate::{
+    analysis::Lookahead,
+    bnf::LoweringError,
+    earley::{Family, Forest, ForestNode, Label},
+    ll1_table::DriverError,
+    lr::{Action, Automaton},
+    prelude::*,
+};
+
+/// Parses a stream of terminals with the LALR(1) automaton of a syntax,
+/// following every action of the conflicting cells.
+pub struct GlrParser {
+    automaton: Automaton,
+}
+
+impl GlrParser {
+    /// Fails if the syntax contains an exception, see [Automaton::lalr1].
+    pub fn new<S: ISyntax>(syntax: &S) -> Result<Self, LoweringError> {
+        Ok(Self {
+            automaton: Automaton::lalr1(syntax)?,
+        })
+    }
+
+    pub fn automaton(&self) -> &Automaton {
+        &self.automaton
+    }
+
+    /// Parses the whole stream, starting from the first rule of the syntax.
+    pub fn parse<I>(&self, tokens: I) -> Result<Forest, DriverError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| token.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        let mut run = Run {
+            automaton: &self.automaton,
+            nodes: Vec::default(),
+            forest: Forest::default(),
+            symbols: HashMap::default(),
+            intermediates: HashMap::default(),
+        };
+
+        if self.automaton.states.is_empty() {
+            return Err(run.error(&tokens, 0, &[]));
+        }
+
+        let mut level = vec![run.add_node(0, 0)];
+
+        for pos in 0..=tokens.len() {
+            let token = tokens.get(pos).map(String::as_str);
+            run.reduce(&mut level, pos, token);
+
+            if pos == tokens.len() {
+                if let Some(root) = run.accept(&level) {
+                    run.forest.root = root;
+                    return Ok(run.forest);
+                }
+            } else {
+                let next = run.shift(&level, pos, &tokens[pos]);
+
+                if !next.is_empty() {
+                    level = next;
+                    continue;
+                }
+            }
+
+            return Err(run.error(&tokens, pos, &level));
+        }
+
+        unreachable!()
+    }
+}
+
+/// A node of the graph-structured stack.
+struct GssNode {
+    state: usize,
+    /// Position at which the state has been reached.
+    pos: usize,
+    /// The nodes below, and the forest node of the symbol in between.
+    edges: Vec<(usize, usize)>,
+}
+
+struct Run<'p> {
+    automaton: &'p Automaton,
+    nodes: Vec<GssNode>,
+    forest: Forest,
+    /// Forest node of each symbol, by non-terminal, start and end.
+    symbols: HashMap<(usize, usize, usize), usize>,
+    /// Forest node of each prefix, by production, length, start and end.
+    intermediates: HashMap<(usize, usize, usize, usize), usize>,
+}
+
+impl Run<'_> {
+    fn add_node(&mut self, state: usize, pos: usize) -> usize {
+        self.nodes.push(GssNode {
+            state,
+            pos,
+            edges: Vec::default(),
+        });
+        self.nodes.len() - 1
+    }
+
+    fn add_forest_node(&mut self, label: Label, span: std::ops::Range<usize>) -> usize {
+        self.forest.nodes.push(ForestNode {
+            label,
+            span,
+            families: Vec::default(),
+        });
+        self.forest.nodes.len() - 1
+    }
+
+    fn actions(&self, node: usize, lookahead: &Lookahead) -> &[Action] {
+        self.automaton.action[self.nodes[node].state]
+            .get(lookahead)
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the lookahead the token stands for in the state of the node.
+    fn lookahead(&self, node: usize, token: Option<&str>) -> Lookahead {
+        self.automaton.lookahead(self.nodes[node].state, token)
+    }
+
+    /// Performs every reduction at the position, until no new node, edge or
+    /// derivation is found.
+    ///
+    /// Reductions are replayed as long as something changes, so that the
+    /// ones going through an edge added afterwards are not missed.
+    fn reduce(&mut self, level: &mut Vec<usize>, pos: usize, token: Option<&str>) {
+        loop {
+            let mut changed = false;
+            let mut i = 0;
+
+            while i < level.len() {
+                let node = level[i];
+                let lookahead = self.lookahead(node, token);
+                let reductions = self
+                    .actions(node, &lookahead)
+                    .iter()
+                    .filter_map(|action| match action {
+                        Action::Reduce(production) => Some(*production),
+                        Action::Shift(_) | Action::Accept => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                for production in reductions {
+                    let len = self.automaton.productions[production].rhs.len();
+
+                    for (below, children) in self.paths(node, len) {
+                        changed |= self.reduce_path(level, pos, production, below, children);
+                    }
+                }
+
+                i += 1;
+            }
+
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    /// Returns the nodes reached by going down len edges, and the forest
+    /// nodes of the edges, from left to right.
+    fn paths(&self, node: usize, len: usize) -> Vec<(usize, Vec<usize>)> {
+        if len == 0 {
+            return vec![(node, Vec::default())];
+        }
+
+        self.nodes[node]
+            .edges
+            .iter()
+            .flat_map(|&(below, symbol)| {
+                self.paths(below, len - 1)
+                    .into_iter()
+                    .map(move |(bottom, mut children)| {
+                        children.push(symbol);
+                        (bottom, children)
+                    })
+            })
+            .collect()
+    }
+
+    fn reduce_path(
+        &mut self,
+        level: &mut Vec<usize>,
+        pos: usize,
+        production: usize,
+        below: usize,
+        children: Vec<usize>,
+    ) -> bool {
+        let lhs = self.automaton.productions[production].lhs;
+        let start = self.nodes[below].pos;
+        let target = self.automaton.goto[self.nodes[below].state][&lhs];
+
+        let (symbol, mut changed) = self.symbol(lhs, start, pos);
+        let family = self.family(production, &children, start);
+        changed |= self.add_family(symbol, family);
+
+        let node = match level.iter().find(|&&node| self.nodes[node].state == target) {
+            Some(&node) => node,
+            None => {
+                let node = self.add_node(target, pos);
+                level.push(node);
+                changed = true;
+                node
+            }
+        };
+
+        if !self.nodes[node].edges.contains(&(below, symbol)) {
+            self.nodes[node].edges.push((below, symbol));
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Returns the forest node of the non-terminal, and true if it is new.
+    fn symbol(&mut self, nt: usize, start: usize, end: usize) -> (usize, bool) {
+        if let Some(&id) = self.symbols.get(&(nt, start, end)) {
+            return (id, false);
+        }
+
+        let non_terminal = &self.automaton.non_terminals[nt];
+        let label = if non_terminal.named {
+            Label::Rule(non_terminal.name.clone())
+        } else {
+            Label::Sequence
+        };
+
+        let id = self.add_forest_node(label, start..end);
+        self.symbols.insert((nt, start, end), id);
+        (id, true)
+    }
+
+    /// Returns the derivation of a production from its children, the
+    /// previous ones being packed into an intermediate node.
+    fn family(&mut self, production: usize, children: &[usize], start: usize) -> Family {
+        let Some((&last, prefix)) = children.split_last() else {
+            return Family {
+                left: None,
+                right: None,
+            };
+        };
+
+        let left = (!prefix.is_empty()).then(|| {
+            let end = self.forest.nodes[prefix[prefix.len() - 1]].span.end;
+            let key = (production, prefix.len(), start, end);
+
+            let id = match self.intermediates.get(&key) {
+                Some(&id) => id,
+                None => {
+                    let id = self.add_forest_node(Label::Intermediate, start..end);
+                    self.intermediates.insert(key, id);
+                    id
+                }
+            };
+
+            let family = self.family(production, prefix, start);
+            self.add_family(id, family);
+            id
+        });
+
+        Family {
+            left,
+            right: Some(last),
+        }
+    }
+
+    fn add_family(&mut self, id: usize, family: Family) -> bool {
+        let families = &mut self.forest.nodes[id].families;
+
+        if families.contains(&family) {
+            false
+        } else {
+            families.push(family);
+            true
+        }
+    }
+
+    fn shift(&mut self, level: &[usize], pos: usize, token: &str) -> Vec<usize> {
+        let mut next = Vec::<usize>::default();
+        // Forest node of the token, as a literal and as a terminal.
+        let mut symbols = [None, None];
+
+        for &node in level.iter() {
+            let lookahead = self.lookahead(node, Some(token));
+            let targets = self
+                .actions(node, &lookahead)
+                .iter()
+                .filter_map(|action| match action {
+                    Action::Shift(target) => Some(*target),
+                    Action::Reduce(_) | Action::Accept => None,
+                })
+                .collect::<Vec<_>>();
+
+            for target in targets {
+                let (slot, label) = match lookahead {
+                    Lookahead::Literal(_) => (0, Label::Literal(token.to_string())),
+                    _ => (1, Label::Terminal(token.to_string())),
+                };
+
+                let symbol =
+                    *symbols[slot].get_or_insert_with(|| self.add_forest_node(label, pos..pos + 1));
+
+                let above = match next.iter().find(|&&n| self.nodes[n].state == target) {
+                    Some(&above) => above,
+                    None => {
+                        let above = self.add_node(target, pos + 1);
+                        next.push(above);
+                        above
+                    }
+                };
+
+                self.nodes[above].edges.push((node, symbol));
+            }
+        }
+
+        next
+    }
+
+    /// Returns the forest node of the start rule, if a node of the last
+    /// position accepts.
+    fn accept(&self, level: &[usize]) -> Option<usize> {
+        level
+            .iter()
+            .find(|&&node| {
+                self.actions(node, &Lookahead::End)
+                    .contains(&Action::Accept)
+            })
+            .and_then(|&node| self.nodes[node].edges.first())
+            .map(|&(_, symbol)| symbol)
+    }
+
+    fn error(&self, tokens: &[String], pos: usize, level: &[usize]) -> DriverError {
+        let mut expected = level
+            .iter()
+            .flat_map(|&node| self.automaton.action[self.nodes[node].state].keys())
+            .map(|lookahead| lookahead.as_terminal().map(str::to_string))
+            .collect::<Vec<_>>();
+
+        expected.sort();
+        expected.dedup();
+
+        DriverError {
+            position: pos,
+            found: tokens.get(pos).cloned(),
+            expected,
+        }
+    }
+}
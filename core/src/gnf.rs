@@ -0,0 +1,342 @@
+//! Greibach Normal Form of a syntax.
+//!
+//! Every alternative of a syntax in GNF starts with a terminal, followed by
+//! meta identifiers only, eg.
+//!
+//! <expr> = "(", <expr>, <close>, <expr_rep> | <number>, <expr_rep>;
+//!
+//! Only the start rule may derive the empty string, and it is then never
+//! used on a rhs. Since every step of a derivation consumes a terminal,
+//! generating or parsing a sentence of n terminals takes at most n steps.
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    left_recursion,
+    normal_form::{NormalGrammar, Sym},
+    prelude::*,
+    syntax::to_syntax,
+    visit::has_exception,
+    DefinitionsList, MetaIdentifier, Primary, Rule, SingleDefinition, Syntax, Term,
+};
+
+/// The result of converting a syntax to GNF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Greibach {
+    pub syntax: Syntax,
+    /// The original rule each rule of the syntax comes from, in the same
+    /// order. None for the rules deriving a single terminal, added for the
+    /// terminals which are not first in their alternative.
+    pub origins: Vec<Option<MetaIdentifier>>,
+}
+
+impl Greibach {
+    /// Returns the original rule the rule comes from.
+    pub fn origin(&self, rule: &str) -> Option<&MetaIdentifier> {
+        let index = self.syntax.iter().position(|r| *r.lhs == *rule)?;
+        self.origins[index].as_ref()
+    }
+}
+
+/// An error raised while converting a syntax to GNF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GnfError {
+    /// The rule contains an exception, which GNF cannot express.
+    Exception(String),
+    /// Left recursion remaining after elimination, eg. behind an exception
+    /// or in a rule with no other alternative.
+    LeftRecursion(Vec<String>),
+}
+
+impl Display for GnfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GnfError::Exception(rule) => {
+                write!(
+                    f,
+                    "<{rule}> contains an exception, which GNF cannot express"
+                )
+            }
+            GnfError::LeftRecursion(cycle) => {
+                let cycle = cycle
+                    .iter()
+                    .map(|rule| format!("<{rule}>"))
+                    .collect::<Vec<_>>();
+
+                write!(f, "left recursion: {}", cycle.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for GnfError {}
+
+/// Converts the syntax to GNF, the start rule being the first one.
+///
+/// Left recursion is first eliminated, see [left_recursion::eliminate], and
+/// the sequences are turned into rules, repetitions being right-recursive.
+/// The empty and unit alternatives are then removed, and the leading meta
+/// identifiers are substituted by their alternatives, until every
+/// alternative starts with a terminal.
+pub fn to_gnf<S: ISyntax>(syntax: &S) -> Result<Greibach, GnfError> {
+    let syntax = to_syntax(syntax);
+
+    if let Some(rule) = syntax.iter().find(|rule| has_exception(&rule.rhs)) {
+        return Err(GnfError::Exception(rule.lhs.to_string()));
+    }
+
+    let syntax = left_recursion::eliminate(&syntax);
+    let mut grammar = flatten(&syntax);
+
+    if grammar.rules.is_empty() {
+        return Ok(Greibach {
+            syntax: Syntax::default(),
+            origins: Vec::default(),
+        });
+    }
+
+    grammar.isolate_start();
+    grammar.remove_empty();
+    grammar.remove_units();
+    grammar.remove_useless();
+    grammar.substitute_leading()?;
+    grammar.lift_terminals();
+    grammar.remove_useless();
+
+    Ok(grammar.into_greibach())
+}
+
+/// The terminals are the literals, the special sequences and the undefined
+/// meta identifiers.
+type Grammar = NormalGrammar<Primary>;
+
+/// Turns the optional, repeated and grouped sequences into rules.
+fn flatten(syntax: &Syntax) -> Grammar {
+    let mut grammar = Grammar::default();
+    let mut index = HashMap::<String, usize>::default();
+
+    for rule in syntax.iter() {
+        if !index.contains_key(&*rule.lhs) {
+            let id = grammar.add_rule(rule.lhs.to_string(), Some(rule.lhs.to_string()));
+            index.insert(rule.lhs.to_string(), id);
+        }
+    }
+
+    for rule in syntax.iter() {
+        let lhs = index[&*rule.lhs];
+
+        for def in rule.rhs.iter() {
+            let def = grammar.single_definition(def, lhs, &index);
+            grammar.add(lhs, def);
+        }
+    }
+
+    grammar
+}
+
+impl Grammar {
+    fn single_definition(
+        &mut self,
+        def: &SingleDefinition,
+        rule: usize,
+        index: &HashMap<String, usize>,
+    ) -> Vec<Sym<Primary>> {
+        let mut symbols = Vec::<Sym<Primary>>::default();
+
+        for term in def.iter() {
+            let symbol = match &term.primary {
+                Primary::Optional(seq) => {
+                    let id = self.anonymous(rule, "opt");
+
+                    for def in seq.iter() {
+                        let def = self.single_definition(def, rule, index);
+                        self.add(id, def);
+                    }
+
+                    self.add(id, Vec::default());
+                    Some(Sym::Rule(id))
+                }
+                // {E} becomes R = E, R | ;
+                Primary::Repeated(seq) => {
+                    let id = self.anonymous(rule, "rep");
+
+                    for def in seq.as_ref().iter() {
+                        let mut def = self.single_definition(def, rule, index);
+                        def.push(Sym::Rule(id));
+                        self.add(id, def);
+                    }
+
+                    self.add(id, Vec::default());
+                    Some(Sym::Rule(id))
+                }
+                Primary::Grouped(seq) => {
+                    let id = self.anonymous(rule, "grp");
+
+                    for def in seq.as_ref().iter() {
+                        let def = self.single_definition(def, rule, index);
+                        self.add(id, def);
+                    }
+
+                    Some(Sym::Rule(id))
+                }
+                Primary::MetaIdentifier(id) if index.contains_key(&**id) => {
+                    Some(Sym::Rule(index[&**id]))
+                }
+                Primary::Empty => None,
+                primary => Some(Sym::Terminal(primary.clone())),
+            };
+
+            let n = term.repetition.unwrap_or(1) as usize;
+            symbols.extend(symbol.into_iter().flat_map(|symbol| vec![symbol; n]));
+        }
+
+        symbols
+    }
+
+    /// Adds a rule named after the rule it is created in.
+    fn anonymous(&mut self, rule: usize, role: &str) -> usize {
+        let name = self.fresh(&format!("{}_{role}", self.rules[rule].name));
+        let origin = self.rules[rule].origin.clone();
+        self.add_rule(name, origin)
+    }
+
+    /// Substitutes the leading meta identifier of every alternative by the
+    /// alternatives of its rule, the latter being converted first.
+    fn substitute_leading(&mut self) -> Result<(), GnfError> {
+        let mut done = vec![false; self.rules.len()];
+
+        for id in 0..self.rules.len() {
+            self.substitute_rule(id, &mut done, &mut Vec::default())?;
+        }
+
+        Ok(())
+    }
+
+    fn substitute_rule(
+        &mut self,
+        id: usize,
+        done: &mut Vec<bool>,
+        path: &mut Vec<usize>,
+    ) -> Result<(), GnfError> {
+        if done[id] {
+            return Ok(());
+        }
+
+        if let Some(at) = path.iter().position(|&r| r == id) {
+            let mut cycle = path[at..]
+                .iter()
+                .map(|&r| self.rules[r].name.clone())
+                .collect::<Vec<_>>();
+            cycle.push(self.rules[id].name.clone());
+            return Err(GnfError::LeftRecursion(cycle));
+        }
+
+        path.push(id);
+
+        let defs = std::mem::take(&mut self.rules[id].defs);
+        let mut substituted = Vec::<Vec<Sym<Primary>>>::default();
+
+        for def in defs {
+            match def.first() {
+                Some(Sym::Rule(leading)) => {
+                    let leading = *leading;
+                    self.substitute_rule(leading, done, path)?;
+
+                    for prefix in self.rules[leading].defs.iter() {
+                        let mut new = prefix.clone();
+                        new.extend(def[1..].iter().cloned());
+
+                        if !substituted.contains(&new) {
+                            substituted.push(new);
+                        }
+                    }
+                }
+                _ => {
+                    if !substituted.contains(&def) {
+                        substituted.push(def);
+                    }
+                }
+            }
+        }
+
+        self.rules[id].defs = substituted;
+        path.pop();
+        done[id] = true;
+        Ok(())
+    }
+
+    /// Replaces the terminals which are not first in their alternative by a
+    /// rule deriving them.
+    fn lift_terminals(&mut self) {
+        let mut lifted = Vec::<(Primary, usize)>::default();
+
+        for id in 0..self.rules.len() {
+            let mut defs = std::mem::take(&mut self.rules[id].defs);
+
+            for symbol in defs.iter_mut().flat_map(|def| def.iter_mut().skip(1)) {
+                let Sym::Terminal(primary) = symbol.clone() else {
+                    continue;
+                };
+
+                let rule = match lifted.iter().find(|(p, _)| *p == primary) {
+                    Some((_, rule)) => *rule,
+                    None => {
+                        let base = match &self.rules[id].origin {
+                            Some(origin) => origin.clone(),
+                            None => self.rules[id].name.clone(),
+                        };
+                        let name = self.fresh(&format!("{base}_term"));
+                        let rule = self.add_rule(name, None);
+                        self.add(rule, vec![Sym::Terminal(primary.clone())]);
+                        lifted.push((primary, rule));
+                        rule
+                    }
+                };
+
+                *symbol = Sym::Rule(rule);
+            }
+
+            self.rules[id].defs = defs;
+        }
+    }
+
+    fn into_greibach(self) -> Greibach {
+        let names = self
+            .rules
+            .iter()
+            .map(|rule| rule.name.clone())
+            .collect::<Vec<_>>();
+
+        let mut origins = Vec::<Option<MetaIdentifier>>::default();
+
+        let syntax = self
+            .rules
+            .into_iter()
+            .map(|rule| {
+                origins.push(rule.origin.map(MetaIdentifier::new));
+
+                let rhs = rule
+                    .defs
+                    .into_iter()
+                    .map(|def| {
+                        if def.is_empty() {
+                            return SingleDefinition::empty();
+                        }
+
+                        def.into_iter()
+                            .map(|symbol| match symbol {
+                                Sym::Rule(id) => {
+                                    Term::from(Primary::from(MetaIdentifier::new(&names[id])))
+                                }
+                                Sym::Terminal(primary) => Term::from(primary),
+                            })
+                            .collect()
+                    })
+                    .collect::<DefinitionsList>();
+
+                Rule::new(MetaIdentifier::new(rule.name), rhs)
+            })
+            .collect();
+
+        Greibach { syntax, origins }
+    }
+}
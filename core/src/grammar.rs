@@ -0,0 +1,197 @@
+//! Plain productions compiled from a syntax, shared by the parsers.
+//!
+//! Each optional, repeated and grouped sequence gets an anonymous
+//! non-terminal, and an exception `X - Y` becomes an anonymous non-terminal
+//! deriving X whose matches must not derive from Y.
+use std::collections::HashMap;
+
+use crate::{Factor, Primary, SingleDefinition, Syntax, Term};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Symbol {
+    NonTerminal(usize),
+    Literal(String),
+    Terminal(String),
+}
+
+impl Symbol {
+    /// Returns the text matched by a literal, or the name of a terminal.
+    pub fn as_terminal(&self) -> Option<&str> {
+        match self {
+            Symbol::NonTerminal(_) => None,
+            Symbol::Literal(value) | Symbol::Terminal(value) => Some(value),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct NonTerminal {
+    /// None for the anonymous ones.
+    pub name: Option<String>,
+    pub productions: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Production {
+    pub lhs: usize,
+    pub rhs: Vec<Symbol>,
+    /// Non-terminal which must not derive what the production matched.
+    pub exception: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Grammar {
+    pub names: HashMap<String, usize>,
+    /// The rules come first, in the order they are defined.
+    pub non_terminals: Vec<NonTerminal>,
+    pub productions: Vec<Production>,
+    pub nullable: Vec<bool>,
+}
+
+impl Grammar {
+    pub fn compile(syntax: Syntax) -> Self {
+        let mut grammar = Self::default();
+
+        for rule in syntax.iter() {
+            if !grammar.names.contains_key(&*rule.lhs) {
+                let id = grammar.add_non_terminal(Some(rule.lhs.to_string()));
+                grammar.names.insert(rule.lhs.to_string(), id);
+            }
+        }
+
+        for rule in syntax.iter() {
+            let lhs = grammar.names[&*rule.lhs];
+
+            for def in rule.rhs.iter() {
+                let rhs = grammar.single_definition(def);
+                grammar.add_production(lhs, rhs, None);
+            }
+        }
+
+        grammar.compute_nullable();
+        grammar
+    }
+
+    fn add_non_terminal(&mut self, name: Option<String>) -> usize {
+        self.non_terminals.push(NonTerminal {
+            name,
+            productions: Vec::default(),
+        });
+        self.non_terminals.len() - 1
+    }
+
+    fn add_production(&mut self, lhs: usize, rhs: Vec<Symbol>, exception: Option<usize>) {
+        self.productions.push(Production {
+            lhs,
+            rhs,
+            exception,
+        });
+        self.non_terminals[lhs]
+            .productions
+            .push(self.productions.len() - 1);
+    }
+
+    fn single_definition(&mut self, def: &SingleDefinition) -> Vec<Symbol> {
+        def.iter().flat_map(|term| self.term(term)).collect()
+    }
+
+    fn term(&mut self, term: &Term) -> Vec<Symbol> {
+        let symbols = self.factor(&term.factor);
+
+        let Some(exception) = &term.exception else {
+            return symbols;
+        };
+
+        let excepted = self.add_non_terminal(None);
+        let rhs = self.factor(exception);
+        self.add_production(excepted, rhs, None);
+
+        let id = self.add_non_terminal(None);
+        self.add_production(id, symbols, Some(excepted));
+        vec![Symbol::NonTerminal(id)]
+    }
+
+    fn factor(&mut self, factor: &Factor) -> Vec<Symbol> {
+        let symbol = self.primary(&factor.primary);
+        let n = factor.repetition.unwrap_or(1) as usize;
+
+        symbol
+            .into_iter()
+            .flat_map(|symbol| vec![symbol; n])
+            .collect()
+    }
+
+    fn primary(&mut self, primary: &Primary) -> Option<Symbol> {
+        let symbol = match primary {
+            Primary::Optional(seq) => {
+                let id = self.add_non_terminal(None);
+
+                for def in seq.iter() {
+                    let rhs = self.single_definition(def);
+                    self.add_production(id, rhs, None);
+                }
+
+                self.add_production(id, Vec::default(), None);
+                Symbol::NonTerminal(id)
+            }
+            // {X} becomes R = X, R | ;
+            Primary::Repeated(seq) => {
+                let id = self.add_non_terminal(None);
+
+                for def in seq.iter() {
+                    let mut rhs = self.single_definition(def);
+                    rhs.push(Symbol::NonTerminal(id));
+                    self.add_production(id, rhs, None);
+                }
+
+                self.add_production(id, Vec::default(), None);
+                Symbol::NonTerminal(id)
+            }
+            Primary::Grouped(seq) => {
+                let id = self.add_non_terminal(None);
+
+                for def in seq.iter() {
+                    let rhs = self.single_definition(def);
+                    self.add_production(id, rhs, None);
+                }
+
+                Symbol::NonTerminal(id)
+            }
+            Primary::MetaIdentifier(id) => match self.names.get(&**id) {
+                Some(&id) => Symbol::NonTerminal(id),
+                None => Symbol::Terminal(id.to_string()),
+            },
+            Primary::Literal(lit) => Symbol::Literal(lit.to_string()),
+            Primary::Special(seq) => Symbol::Terminal(seq.to_string()),
+            Primary::Empty => return None,
+        };
+
+        Some(symbol)
+    }
+
+    fn compute_nullable(&mut self) {
+        self.nullable = vec![false; self.non_terminals.len()];
+
+        loop {
+            let mut changed = false;
+
+            for prod in self.productions.iter() {
+                let nullable = !self.nullable[prod.lhs]
+                    && prod.rhs.iter().all(|symbol| match symbol {
+                        Symbol::NonTerminal(id) => self.nullable[*id],
+                        Symbol::Literal(_) | Symbol::Terminal(_) => false,
+                    })
+                    && !prod.exception.is_some_and(|id| self.nullable[id]);
+
+                if nullable {
+                    self.nullable[prod.lhs] = true;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+    }
+}
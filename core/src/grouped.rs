@@ -35,6 +35,10 @@ impl<'a> AsRef<DefinitionsListRef<'a>> for GroupedSequenceRef<'a> {
 pub struct GroupedSequence(DefinitionsList);
 
 impl GroupedSequence {
+    pub fn new(defs: DefinitionsList) -> Self {
+        Self(defs)
+    }
+
     pub fn is_parsable(input: &syn::parse::ParseStream) -> bool {
         input.peek(syn::token::Paren)
     }
@@ -0,0 +1,439 @@
+//! Backtracking recursive-descent interpreter of a syntax.
+//!
+//! Parses text directly against a syntax, without generating code. Each
+//! alternative is tried in order, and a failure later in the input backtracks
+//! into the previous choices, so the first complete parse is returned.
+//!
+//! Left-recursive rules never match, see [crate::left_recursion::eliminate]
+//! to rewrite them first. The running time can be exponential on grammars
+//! which need a lot of backtracking.
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeSet, HashMap, HashSet},
+};
+
+use crate::{
+    cst::Node, parser::ParseError, prelude::*, syntax::to_syntax, DefinitionsList, Factor, Primary,
+    Syntax, Term,
+};
+
+/// Matches a terminal at the start of the input, returning its length in
+/// bytes.
+pub type Matcher = Box<dyn Fn(&str) -> Option<usize>>;
+
+/// Interprets a syntax to parse text.
+///
+/// Literals are matched as is. Meta identifiers which are not defined by a
+/// rule, and special sequences, are matched with the [Matcher] registered
+/// under their name.
+pub struct Interpreter {
+    syntax: Syntax,
+    /// Rules of the syntax defining each name, indexed by name.
+    names: HashMap<String, usize>,
+    rules: Vec<(String, Vec<usize>)>,
+    terminals: HashMap<String, Matcher>,
+    skip_whitespace: bool,
+}
+
+impl Interpreter {
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let syntax = to_syntax(syntax);
+        let mut names = HashMap::<String, usize>::default();
+        let mut rules = Vec::<(String, Vec<usize>)>::default();
+
+        for (position, rule) in syntax.iter().enumerate() {
+            let index = *names.entry(rule.lhs.to_string()).or_insert_with(|| {
+                rules.push((rule.lhs.to_string(), Vec::default()));
+                rules.len() - 1
+            });
+
+            rules[index].1.push(position);
+        }
+
+        Self {
+            syntax,
+            names,
+            rules,
+            terminals: HashMap::default(),
+            skip_whitespace: false,
+        }
+    }
+
+    /// Registers the matcher of a terminal, either the name of an undefined
+    /// meta identifier or the text of a special sequence.
+    pub fn terminal<S, F>(mut self, name: S, matcher: F) -> Self
+    where
+        S: ToString,
+        F: Fn(&str) -> Option<usize> + 'static,
+    {
+        self.terminals.insert(name.to_string(), Box::new(matcher));
+        self
+    }
+
+    /// Skips whitespaces before each terminal and at the end of the input.
+    pub fn skip_whitespace(mut self, skip: bool) -> Self {
+        self.skip_whitespace = skip;
+        self
+    }
+
+    /// Parses the whole input, starting from the first rule of the syntax.
+    pub fn parse(&self, input: &str) -> Result<Node, ParseError> {
+        match self.syntax.first() {
+            Some(rule) => self.parse_rule(&rule.lhs, input),
+            None => Err(ParseError::at(input, 0, "the syntax is empty")),
+        }
+    }
+
+    /// Parses the whole input, starting from the given rule.
+    pub fn parse_rule(&self, rule: &str, input: &str) -> Result<Node, ParseError> {
+        let Some(&index) = self.names.get(rule) else {
+            return Err(ParseError::at(input, 0, format!("<{rule}> is not defined")));
+        };
+
+        let run = Run {
+            interpreter: self,
+            input,
+            furthest: Cell::new(0),
+            expected: RefCell::default(),
+            active: RefCell::default(),
+            quiet: Cell::new(0),
+        };
+
+        let mut nodes = Vec::<Node>::default();
+        let start = run.skip(0);
+
+        let matched = run.rule(index, start, &mut nodes, &mut |end, _| {
+            let end = run.skip(end);
+
+            if end == input.len() {
+                true
+            } else {
+                run.fail(end, "end of input".to_string());
+                false
+            }
+        });
+
+        match nodes.pop() {
+            Some(node) if matched => Ok(node),
+            _ => {
+                let expected = run.expected.into_inner().into_iter().collect::<Vec<_>>();
+
+                Err(ParseError::at(
+                    input,
+                    run.furthest.get(),
+                    format!("expecting {}", expected.join(" or ")),
+                ))
+            }
+        }
+    }
+}
+
+/// Called with the end of a match and the nodes parsed so far, returns true
+/// if the rest of the input has been parsed.
+type Continuation<'c> = &'c mut dyn FnMut(usize, &mut Vec<Node>) -> bool;
+
+/// The matches of an iteration of a repeated sequence left to try, see
+/// [Run::repeated].
+struct Iteration {
+    pos: usize,
+    /// Length of the nodes before the iteration.
+    len: usize,
+    matches: std::vec::IntoIter<(usize, Vec<Node>)>,
+}
+
+struct Run<'r> {
+    interpreter: &'r Interpreter,
+    input: &'r str,
+    /// Furthest position at which a terminal failed, and what was expected.
+    furthest: Cell<usize>,
+    expected: RefCell<BTreeSet<String>>,
+    /// Rules being parsed at a position, to stop on left recursion.
+    active: RefCell<HashSet<(usize, usize)>>,
+    /// Failures are not reported while checking exceptions.
+    quiet: Cell<usize>,
+}
+
+impl<'r> Run<'r> {
+    fn skip(&self, pos: usize) -> usize {
+        if self.interpreter.skip_whitespace {
+            let rest = &self.input[pos..];
+            pos + rest.len() - rest.trim_start().len()
+        } else {
+            pos
+        }
+    }
+
+    fn fail(&self, pos: usize, expected: String) {
+        if self.quiet.get() > 0 || pos < self.furthest.get() {
+            return;
+        }
+
+        let mut set = self.expected.borrow_mut();
+
+        if pos > self.furthest.get() {
+            self.furthest.set(pos);
+            set.clear();
+        }
+
+        set.insert(expected);
+    }
+
+    fn rule(&self, index: usize, pos: usize, nodes: &mut Vec<Node>, k: Continuation) -> bool {
+        let key = (index, pos);
+        let (name, rhs) = &self.interpreter.rules[index];
+
+        if !self.active.borrow_mut().insert(key) {
+            return false;
+        }
+
+        let mut matched = false;
+
+        for &rule in rhs.iter() {
+            let defs = &self.interpreter.syntax[rule].rhs;
+            let mut children = Vec::<Node>::default();
+
+            matched = self.definitions_list(defs, pos, &mut children, &mut |end, children| {
+                // The rule may start again at the same position once matched.
+                self.active.borrow_mut().remove(&key);
+                // Skipped whitespaces are not part of the rule.
+                let start = children.first().map_or(pos, |child| child.span.start);
+                nodes.push(Node::rule(name, start..end, children.clone()));
+
+                let matched = k(end, nodes);
+
+                if !matched {
+                    nodes.pop();
+                }
+
+                self.active.borrow_mut().insert(key);
+                matched
+            });
+
+            if matched {
+                break;
+            }
+        }
+
+        self.active.borrow_mut().remove(&key);
+        matched
+    }
+
+    fn definitions_list(
+        &self,
+        defs: &'r DefinitionsList,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        for def in defs.iter() {
+            let len = nodes.len();
+
+            if self.terms(def, pos, nodes, k) {
+                return true;
+            }
+
+            nodes.truncate(len);
+        }
+
+        false
+    }
+
+    fn terms(&self, terms: &'r [Term], pos: usize, nodes: &mut Vec<Node>, k: Continuation) -> bool {
+        match terms.split_first() {
+            None => k(pos, nodes),
+            Some((term, rest)) => self.term(term, pos, nodes, &mut |end, nodes| {
+                self.terms(rest, end, nodes, k)
+            }),
+        }
+    }
+
+    fn term(&self, term: &'r Term, pos: usize, nodes: &mut Vec<Node>, k: Continuation) -> bool {
+        match &term.exception {
+            None => self.factor(&term.factor, pos, nodes, k),
+            Some(exception) => self.factor(&term.factor, pos, nodes, &mut |end, nodes| {
+                !self.matches_exactly(exception, pos, end) && k(end, nodes)
+            }),
+        }
+    }
+
+    /// Returns true if the factor can match exactly the input between start
+    /// and end.
+    fn matches_exactly(&self, factor: &'r Factor, start: usize, end: usize) -> bool {
+        self.quiet.set(self.quiet.get() + 1);
+
+        let matched = self.factor(factor, start, &mut Vec::default(), &mut |pos, _| {
+            pos == end || self.skip(pos) == end
+        });
+
+        self.quiet.set(self.quiet.get() - 1);
+        matched
+    }
+
+    fn factor(
+        &self,
+        factor: &'r Factor,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        self.repeat(
+            &factor.primary,
+            factor.repetition.unwrap_or(1),
+            pos,
+            nodes,
+            k,
+        )
+    }
+
+    /// Matches the primary exactly n times.
+    fn repeat(
+        &self,
+        primary: &'r Primary,
+        n: u32,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        if n == 0 {
+            return k(pos, nodes);
+        }
+
+        self.primary(primary, pos, nodes, &mut |end, nodes| {
+            self.repeat(primary, n - 1, end, nodes, k)
+        })
+    }
+
+    fn primary(
+        &self,
+        primary: &'r Primary,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        match primary {
+            Primary::Optional(seq) => {
+                let len = nodes.len();
+
+                self.definitions_list(seq, pos, nodes, k) || {
+                    nodes.truncate(len);
+                    k(pos, nodes)
+                }
+            }
+            Primary::Repeated(seq) => self.repeated(seq.as_ref(), pos, nodes, k),
+            Primary::Grouped(seq) => self.definitions_list(seq, pos, nodes, k),
+            Primary::MetaIdentifier(id) => match self.interpreter.names.get(&**id) {
+                Some(&index) => self.rule(index, pos, nodes, k),
+                None => self.terminal(id, format!("<{}>", &**id), pos, nodes, k),
+            },
+            Primary::Literal(lit) => {
+                let pos = self.skip(pos);
+
+                if self.input[pos..].starts_with(&**lit) {
+                    let end = pos + lit.len();
+                    self.token(Node::literal(&**lit, pos..end), nodes, k)
+                } else {
+                    self.fail(pos, format!("{:?}", &**lit));
+                    false
+                }
+            }
+            Primary::Special(seq) => self.terminal(seq, format!("? {} ?", &**seq), pos, nodes, k),
+            Primary::Empty => k(pos, nodes),
+        }
+    }
+
+    /// Matches the sequence zero or more times, the longest first.
+    ///
+    /// The iterations are backtracked with an explicit stack rather than
+    /// through the continuations, so that the call stack does not grow with
+    /// the number of iterations: every match of an iteration is collected
+    /// before trying the next one.
+    fn repeated(
+        &self,
+        seq: &'r DefinitionsList,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        let mut stack = vec![self.iteration(seq, pos, nodes.len())];
+
+        while let Some(top) = stack.last_mut() {
+            nodes.truncate(top.len);
+
+            match top.matches.next() {
+                Some((end, children)) => {
+                    nodes.extend(children);
+                    let iteration = self.iteration(seq, end, nodes.len());
+                    stack.push(iteration);
+                }
+                // No more iterations from there, the rest of the input is
+                // tried before backtracking into the previous one.
+                None => {
+                    if k(top.pos, nodes) {
+                        return true;
+                    }
+
+                    stack.pop();
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the matches of one iteration of the sequence, in the order
+    /// they are tried. An iteration matching nothing would loop forever.
+    fn iteration(&self, seq: &'r DefinitionsList, pos: usize, len: usize) -> Iteration {
+        let mut matches = Vec::<(usize, Vec<Node>)>::default();
+
+        self.definitions_list(seq, pos, &mut Vec::default(), &mut |end, nodes| {
+            if end != pos {
+                matches.push((end, nodes.clone()));
+            }
+
+            false
+        });
+
+        Iteration {
+            pos,
+            len,
+            matches: matches.into_iter(),
+        }
+    }
+
+    fn terminal(
+        &self,
+        name: &str,
+        expected: String,
+        pos: usize,
+        nodes: &mut Vec<Node>,
+        k: Continuation,
+    ) -> bool {
+        let pos = self.skip(pos);
+
+        let matched = self
+            .interpreter
+            .terminals
+            .get(name)
+            .and_then(|matcher| matcher(&self.input[pos..]));
+
+        match matched {
+            Some(len) => self.token(Node::terminal(name, pos..pos + len), nodes, k),
+            None => {
+                self.fail(pos, expected);
+                false
+            }
+        }
+    }
+
+    fn token(&self, node: Node, nodes: &mut Vec<Node>, k: Continuation) -> bool {
+        let end = node.span.end;
+        nodes.push(node);
+
+        if k(end, nodes) {
+            true
+        } else {
+            nodes.pop();
+            false
+        }
+    }
+}
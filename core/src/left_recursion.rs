@@ -0,0 +1,399 @@
+//! Left recursion detection and elimination.
+//!
+//! A rule is left-recursive if one of its derivations can start with itself,
+//! either directly (`<a> = <a>, "x";`) or through other rules
+//! (`<a> = <b>; <b> = [<c>], <a>;`). Symbols preceded by nullable ones are in
+//! left position, see [Analysis::transitive].
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use crate::{
+    analysis::Analysis,
+    prelude::*,
+    single_definition::{is_empty, sequence},
+    syntax::to_syntax,
+    DefinitionsList, Factor, GroupedSequence, Primary, RepeatedSequence, Rule, SingleDefinition,
+    Syntax, Term,
+};
+
+/// A left recursion cycle.
+///
+/// Each rule can start with the next one, and the last one with the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeftRecursion {
+    pub cycle: Vec<String>,
+}
+
+impl LeftRecursion {
+    /// Returns true if the rule directly starts with itself.
+    pub fn is_direct(&self) -> bool {
+        self.cycle.len() == 1
+    }
+}
+
+impl Display for LeftRecursion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rule in self.cycle.iter() {
+            write!(f, "<{rule}> -> ")?;
+        }
+
+        write!(f, "<{}>", self.cycle[0])
+    }
+}
+
+/// Returns the left recursion cycles of the syntax.
+///
+/// There is at most one cycle per rule, the shortest one going through it.
+/// Cycles start with the rule defined first.
+pub fn find_cycles<S: ISyntax>(syntax: &S) -> Vec<LeftRecursion> {
+    find_cycles_with(syntax, &Analysis::new(syntax))
+}
+
+/// Returns the left recursion cycles of the syntax, using an existing
+/// analysis.
+pub fn find_cycles_with<S: ISyntax>(syntax: &S, analysis: &Analysis) -> Vec<LeftRecursion> {
+    let mut order = Vec::<String>::default();
+
+    for rule in syntax.as_ref() {
+        let lhs = rule.lhs().to_string();
+
+        if !order.contains(&lhs) {
+            order.push(lhs);
+        }
+    }
+
+    let graph = order
+        .iter()
+        .map(|lhs| {
+            let mut corners = Vec::<&str>::default();
+
+            for id in analysis.transitive(lhs) {
+                if analysis.is_defined(id) && !corners.contains(&&**id) {
+                    corners.push(id);
+                }
+            }
+
+            (lhs.as_str(), corners)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let position = order
+        .iter()
+        .enumerate()
+        .map(|(i, lhs)| (lhs.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    let mut cycles = Vec::<LeftRecursion>::default();
+
+    for lhs in order.iter() {
+        let Some(mut cycle) = shortest_cycle(&graph, lhs) else {
+            continue;
+        };
+
+        let first = (0..cycle.len())
+            .min_by_key(|&i| position[cycle[i]])
+            .unwrap_or_default();
+
+        cycle.rotate_left(first);
+
+        let cycle = LeftRecursion {
+            cycle: cycle.into_iter().map(str::to_string).collect(),
+        };
+
+        if !cycles.contains(&cycle) {
+            cycles.push(cycle);
+        }
+    }
+
+    cycles
+}
+
+/// Returns true if any rule of the syntax is left-recursive.
+pub fn is_left_recursive<S: ISyntax>(syntax: &S) -> bool {
+    !find_cycles(syntax).is_empty()
+}
+
+/// Breadth-first search of the shortest path from the rule back to itself.
+fn shortest_cycle<'a>(
+    graph: &HashMap<&'a str, Vec<&'a str>>,
+    start: &'a str,
+) -> Option<Vec<&'a str>> {
+    let mut parents = HashMap::<&str, &str>::default();
+    let mut visited = HashSet::<&str>::default();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(lhs) = queue.pop_front() {
+        for &next in graph.get(lhs).into_iter().flatten() {
+            if next == start {
+                let mut cycle = vec![lhs];
+
+                while let Some(&parent) = parents.get(cycle[cycle.len() - 1]) {
+                    cycle.push(parent);
+                }
+
+                cycle.reverse();
+                return Some(cycle);
+            }
+
+            if visited.insert(next) {
+                parents.insert(next, lhs);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Removes the left recursion of the syntax, keeping the rule names.
+///
+/// Rules taking part in a cycle are processed in the order they are defined.
+/// Leading rules processed before are substituted, then direct left
+/// recursion is rewritten with a repetition instead of a tail rule:
+///
+/// <a> = <a>, "+", <b> | <b>;
+///
+/// becomes
+///
+/// <a> = <b>, {"+", <b>};
+///
+/// Left-recursive rules defined several times are merged. A rule which only
+/// has left-recursive alternatives derives nothing and is kept as is, so is
+/// left recursion hidden behind an exception.
+pub fn eliminate<S: ISyntax>(syntax: &S) -> Syntax {
+    let syntax = to_syntax(syntax);
+    let analysis = Analysis::new(&syntax);
+    let cycles = find_cycles_with(&syntax, &analysis);
+
+    let mut order = Vec::<&str>::default();
+
+    for rule in syntax.iter() {
+        let in_cycle = cycles
+            .iter()
+            .any(|recursion| recursion.cycle.iter().any(|lhs| *lhs == *rule.lhs));
+
+        if in_cycle && !order.contains(&&*rule.lhs) {
+            order.push(&rule.lhs);
+        }
+    }
+
+    let reached = order
+        .iter()
+        .map(|&lhs| (lhs, reachable(&analysis, lhs)))
+        .collect::<HashMap<_, _>>();
+
+    let mut rewritten = HashMap::<&str, DefinitionsList>::default();
+
+    for (i, &lhs) in order.iter().enumerate() {
+        // Rules processed so far, from the same cycles.
+        let targets = order[..=i]
+            .iter()
+            .copied()
+            .filter(|&other| reached[lhs].contains(other) && reached[other].contains(lhs))
+            .collect::<HashSet<_>>();
+
+        let eliminator = Eliminator {
+            analysis: &analysis,
+            lhs,
+            targets,
+            rewritten: &rewritten,
+        };
+
+        let defs = analysis
+            .rhs(lhs)
+            .into_iter()
+            .flat_map(|rhs| rhs.iter())
+            .flat_map(|def| eliminator.expose(def.to_vec(), &mut Vec::default()))
+            .fold(DefinitionsList::default(), |mut defs, def| {
+                // Expanding nullable prefixes can give the same alternative twice.
+                if !defs.contains(&def) {
+                    defs.push(def);
+                }
+                defs
+            });
+
+        let defs = eliminate_direct(lhs, defs);
+        rewritten.insert(lhs, defs);
+    }
+
+    let mut emitted = HashSet::<&str>::default();
+
+    syntax
+        .iter()
+        .filter_map(|rule| match rewritten.get(&*rule.lhs) {
+            Some(rhs) => emitted
+                .insert(&rule.lhs)
+                .then(|| Rule::new(rule.lhs.clone(), rhs.clone())),
+            None => Some(rule.clone()),
+        })
+        .collect()
+}
+
+/// Returns the rules a derivation of the rule can start with.
+fn reachable<'a>(analysis: &'a Analysis, lhs: &'a str) -> HashSet<&'a str> {
+    let mut reached = HashSet::<&str>::default();
+    let mut stack = vec![lhs];
+
+    while let Some(lhs) = stack.pop() {
+        for id in analysis.transitive(lhs) {
+            if analysis.is_defined(id) && reached.insert(id) {
+                stack.push(id);
+            }
+        }
+    }
+
+    reached
+}
+
+struct Eliminator<'a> {
+    analysis: &'a Analysis,
+    lhs: &'a str,
+    /// The rule itself and the ones to substitute when in left position.
+    targets: HashSet<&'a str>,
+    rewritten: &'a HashMap<&'a str, DefinitionsList>,
+}
+
+impl Eliminator<'_> {
+    /// Rewrites the sequence into alternatives which either start with the
+    /// rule itself, or cannot start with any of the targets.
+    fn expose(&self, terms: Vec<Term>, path: &mut Vec<String>) -> Vec<SingleDefinition> {
+        let Some(first) = terms.first().filter(|_| self.reaches_target(&terms)) else {
+            return vec![sequence(terms)];
+        };
+
+        let rest = &terms[1..];
+
+        if first.exception.is_some() {
+            return vec![sequence(terms)];
+        }
+
+        match first.repetition {
+            Some(0) => return self.expose(rest.to_vec(), path),
+            // n * X, becomes X, (n - 1) * X.
+            Some(n) if n > 1 => {
+                let mut unrolled = vec![
+                    Term::from(first.primary.clone()),
+                    Term::from(Factor {
+                        primary: first.primary.clone(),
+                        repetition: (n > 2).then_some(n - 1),
+                    }),
+                ];
+                unrolled.extend_from_slice(rest);
+                return self.expose(unrolled, path);
+            }
+            _ => {}
+        }
+
+        match &first.primary {
+            Primary::MetaIdentifier(id) if **id == *self.lhs || path.contains(&id.to_string()) => {
+                vec![sequence(terms)]
+            }
+            Primary::MetaIdentifier(id) => {
+                let rhs = if self.targets.contains(&**id) {
+                    self.rewritten.get(&**id)
+                } else if self.analysis.is_nullable(id) {
+                    // Hides what follows, eg. <a> = <b>, <a>; <b> = [<c>];
+                    self.analysis.rhs(id)
+                } else {
+                    None
+                };
+
+                match rhs {
+                    Some(rhs) => {
+                        path.push(id.to_string());
+                        let defs = self.expand(rhs, rest, path);
+                        path.pop();
+                        defs
+                    }
+                    None => vec![sequence(terms)],
+                }
+            }
+            Primary::Grouped(seq) => self.expand(seq, rest, path),
+            Primary::Optional(seq) => {
+                let mut defs = self.expand(seq, rest, path);
+                defs.extend(self.expose(rest.to_vec(), path));
+                defs
+            }
+            // {X}, Y becomes X, {X}, Y | Y.
+            Primary::Repeated(seq) => {
+                let mut tail = vec![first.clone()];
+                tail.extend_from_slice(rest);
+
+                let mut defs = self.expand(seq.as_ref(), &tail, path);
+                defs.extend(self.expose(rest.to_vec(), path));
+                defs
+            }
+            Primary::Empty => self.expose(rest.to_vec(), path),
+            Primary::Literal(_) | Primary::Special(_) => vec![sequence(terms)],
+        }
+    }
+
+    /// Exposes each alternative followed by the rest of the sequence.
+    fn expand(
+        &self,
+        defs: &DefinitionsList,
+        rest: &[Term],
+        path: &mut Vec<String>,
+    ) -> Vec<SingleDefinition> {
+        defs.iter()
+            .flat_map(|def| {
+                let mut terms = def.to_vec();
+                terms.extend_from_slice(rest);
+                self.expose(terms, path)
+            })
+            .collect()
+    }
+
+    fn reaches_target(&self, terms: &[Term]) -> bool {
+        let def = terms.iter().cloned().collect::<SingleDefinition>();
+
+        self.analysis
+            .transitive_of_definitions_list(&DefinitionsList::from_iter([def]))
+            .into_iter()
+            .any(|id| self.targets.contains(&**id))
+    }
+}
+
+/// Rewrites <a> = <a>, x | <a>, y | z | w; into <a> = (z | w), {x | y};
+fn eliminate_direct(lhs: &str, defs: DefinitionsList) -> DefinitionsList {
+    let (recursive, base): (Vec<_>, Vec<_>) = defs.iter().cloned().partition(|def| {
+        def.first().is_some_and(|term| {
+            term.exception.is_none()
+                && term.repetition.is_none()
+                && matches!(&term.primary, Primary::MetaIdentifier(id) if **id == *lhs)
+        })
+    });
+
+    if recursive.is_empty() || base.is_empty() {
+        return defs;
+    }
+
+    // <a> = <a>; is dropped.
+    let tails = recursive
+        .into_iter()
+        .map(|def| sequence(def[1..].to_vec()))
+        .filter(|def| !is_empty(def))
+        .fold(DefinitionsList::default(), |mut tails, def| {
+            if !tails.contains(&def) {
+                tails.push(def);
+            }
+            tails
+        });
+
+    if tails.is_empty() {
+        return base.into_iter().collect();
+    }
+
+    let mut terms = match <[_; 1]>::try_from(base) {
+        Ok([def]) => def.to_vec(),
+        Err(base) => vec![Term::from(Primary::Grouped(GroupedSequence::new(
+            base.into_iter().collect(),
+        )))],
+    };
+
+    terms.push(Term::from(Primary::Repeated(RepeatedSequence::new(tails))));
+
+    DefinitionsList::from_iter([sequence(terms)])
+}
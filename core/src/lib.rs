@@ -1,20 +1,45 @@
+mod classify;
+mod grammar;
 mod itertools;
+mod normal_form;
+mod visit;
 
+pub mod analysis;
 pub mod bnf;
+pub mod cnf;
+pub mod cst;
 pub mod definitions_list;
+pub mod earley;
 pub mod exception;
 pub mod factor;
+pub mod factoring;
+pub mod generator;
+pub mod glr;
+pub mod gnf;
 pub mod grouped;
+pub mod interpreter;
+pub mod left_recursion;
+pub mod lifting;
 pub mod literal;
+pub mod ll1;
+pub mod ll1_table;
+pub mod lr;
 pub mod meta_identifier;
 pub mod optional;
+pub mod packrat;
+pub mod parser;
 pub mod prelude;
 pub mod primary;
+pub mod printer;
 pub mod repeated;
 pub mod rule;
+pub mod sentences;
 pub mod single_definition;
+pub mod span;
+pub mod special;
 pub mod syntax;
 pub mod term;
+pub mod validation;
 
 pub use definitions_list::*;
 pub use exception::*;
@@ -27,6 +52,7 @@ pub use primary::*;
 pub use repeated::*;
 pub use rule::*;
 pub use single_definition::*;
+pub use special::*;
 pub use syntax::*;
 pub use term::*;
 
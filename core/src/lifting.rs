@@ -0,0 +1,269 @@
+//! Lifting of a BNF syntax back to EBNF, eg. to present a grammar imported
+//! from a textbook in a compact form.
+//!
+//! <list> = <item> | <list>, ",", <item>;
+//!
+//! becomes
+//!
+//! <list> = <item>, {",", <item>};
+//!
+//! Recursive rules of the form `X = B | X E` or `X = B | E X` become
+//! repetitions, rules with an empty alternative become optional sequences,
+//! the rules used once are inlined, and the common prefixes of alternatives
+//! are factored out, see [crate::factoring].
+use std::collections::HashMap;
+
+use crate::{
+    bnf::{BnfSyntax, BnfTerm},
+    factoring::left_factor,
+    single_definition::{is_empty, is_empty_term, sequence},
+    visit::walk_primaries,
+    DefinitionsList, GroupedSequence, Literal, MetaIdentifier, OptionalSequence, Primary,
+    RepeatedSequence, Rule, SingleDefinition, Syntax, Term,
+};
+
+impl From<BnfSyntax> for Syntax {
+    fn from(value: BnfSyntax) -> Self {
+        lift(&value)
+    }
+}
+
+/// Lifts the BNF syntax to EBNF, the first rule staying the start one.
+pub fn lift(syntax: &BnfSyntax) -> Syntax {
+    let mut rules = Vec::<(String, DefinitionsList)>::default();
+
+    // The alternatives of a lhs defined more than once are merged.
+    for rule in syntax.iter() {
+        let defs = rule.rhs.iter().map(|def| {
+            sequence(
+                def.iter()
+                    .map(|term| match term {
+                        BnfTerm::Symbol(symbol) => {
+                            Term::from(Primary::MetaIdentifier(MetaIdentifier::new(&**symbol)))
+                        }
+                        BnfTerm::Literal(literal) => {
+                            Term::from(Primary::Literal(Literal::new(&**literal)))
+                        }
+                    })
+                    .collect(),
+            )
+        });
+
+        match rules.iter_mut().find(|(lhs, _)| *lhs == *rule.lhs) {
+            Some((_, rhs)) => rhs.extend(defs),
+            None => rules.push((rule.lhs.to_string(), defs.collect())),
+        }
+    }
+
+    for (lhs, rhs) in rules.iter_mut() {
+        *rhs = lift_rule(lhs, std::mem::take(rhs));
+    }
+
+    while let Some(index) = find_inlinable(&rules) {
+        let (name, rhs) = rules.remove(index);
+
+        for (_, defs) in rules.iter_mut() {
+            *defs = inline(std::mem::take(defs), &name, &rhs);
+        }
+    }
+
+    rules
+        .into_iter()
+        .map(|(lhs, rhs)| Rule::new(MetaIdentifier::new(lhs), left_factor(&rhs)))
+        .collect()
+}
+
+/// Turns the recursion of the rule into a repetition, or its empty
+/// alternative into an optional sequence.
+fn lift_rule(lhs: &str, defs: DefinitionsList) -> DefinitionsList {
+    let (recursive, base) = defs
+        .iter()
+        .cloned()
+        .partition::<Vec<_>, _>(|def| references(def, lhs));
+
+    if recursive.is_empty() {
+        let (empty, others) = base.into_iter().partition::<Vec<_>, _>(is_empty);
+
+        if empty.is_empty() || others.is_empty() {
+            return defs;
+        }
+
+        let optional = Primary::Optional(OptionalSequence::new(others.into_iter().collect()));
+        return DefinitionsList::from_iter([sequence(vec![Term::from(optional)])]);
+    }
+
+    // The rule derives no terminal string, a repetition would derive the
+    // empty one.
+    if base.is_empty() {
+        return defs;
+    }
+
+    let is_reference = |term: &Term| {
+        term.exception.is_none()
+            && term.repetition.is_none()
+            && matches!(&term.primary, Primary::MetaIdentifier(id) if &**id == lhs)
+    };
+
+    // X = B | X E becomes X = B, {E}.
+    let left = recursive
+        .iter()
+        .map(|def| match def.split_first() {
+            Some((first, rest)) if is_reference(first) && !references(rest, lhs) => {
+                Some(sequence(rest.to_vec()))
+            }
+            _ => None,
+        })
+        .collect::<Option<DefinitionsList>>();
+
+    // X = B | E X becomes X = {E}, B.
+    let right = recursive
+        .iter()
+        .map(|def| match def.split_last() {
+            Some((last, rest)) if is_reference(last) && !references(rest, lhs) => {
+                Some(sequence(rest.to_vec()))
+            }
+            _ => None,
+        })
+        .collect::<Option<DefinitionsList>>();
+
+    let base = as_terms(base.into_iter().collect());
+
+    let terms = match (left, right) {
+        (Some(tails), _) => {
+            let mut terms = base;
+            terms.push(Term::from(Primary::Repeated(RepeatedSequence::new(tails))));
+            terms
+        }
+        (None, Some(heads)) => {
+            let mut terms = vec![Term::from(Primary::Repeated(RepeatedSequence::new(heads)))];
+            terms.extend(base);
+            terms
+        }
+        (None, None) => return defs,
+    };
+
+    DefinitionsList::from_iter([sequence(terms)])
+}
+
+/// Returns the terms matching the alternatives: their terms if there is
+/// only one, an optional sequence if one of them is empty, or a group.
+fn as_terms(defs: DefinitionsList) -> Vec<Term> {
+    if defs.len() == 1 {
+        return defs
+            .into_iter()
+            .flatten()
+            .filter(|term| !is_empty_term(term))
+            .collect();
+    }
+
+    let (empty, others) = defs.into_iter().partition::<Vec<_>, _>(is_empty);
+    let others = others.into_iter().collect::<DefinitionsList>();
+
+    let primary = match (empty.is_empty(), others.is_empty()) {
+        (_, true) => return Vec::default(),
+        (true, false) => Primary::Grouped(GroupedSequence::new(others)),
+        (false, false) => Primary::Optional(OptionalSequence::new(others)),
+    };
+
+    vec![Term::from(primary)]
+}
+
+/// Returns a rule used once, which is neither the start one nor recursive.
+fn find_inlinable(rules: &[(String, DefinitionsList)]) -> Option<usize> {
+    let mut uses = HashMap::<&str, usize>::default();
+
+    for (_, defs) in rules.iter() {
+        walk_primaries(defs, &mut |primary| {
+            if let Primary::MetaIdentifier(id) = primary {
+                *uses.entry(&**id).or_default() += 1;
+            }
+        });
+    }
+
+    (1..rules.len()).find(|&i| {
+        let (lhs, defs) = &rules[i];
+        uses.get(lhs.as_str()) == Some(&1) && !defs.iter().any(|def| references(def, lhs))
+    })
+}
+
+/// Replaces the references to the rule by its alternatives.
+fn inline(defs: DefinitionsList, name: &str, rhs: &DefinitionsList) -> DefinitionsList {
+    let mut inlined = DefinitionsList::default();
+
+    for def in defs.into_iter() {
+        let mut terms = Vec::<Term>::default();
+
+        for term in def.into_iter() {
+            terms.extend(inline_term(term, name, rhs));
+        }
+
+        // An alternative made of the reference only gets the alternatives
+        // of the rule.
+        match terms.as_slice() {
+            [Term {
+                factor,
+                exception: None,
+            }] if factor.repetition.is_none() => match &factor.primary {
+                Primary::Grouped(seq) => inlined.extend(seq.as_ref().iter().cloned()),
+                _ => inlined.push(sequence(terms)),
+            },
+            _ => inlined.push(sequence(terms)),
+        }
+    }
+
+    inlined
+}
+
+fn inline_term(term: Term, name: &str, rhs: &DefinitionsList) -> Vec<Term> {
+    let Term {
+        mut factor,
+        exception,
+    } = term;
+
+    factor.primary = match factor.primary {
+        Primary::MetaIdentifier(id) if &*id == name => {
+            let terms = as_terms(rhs.clone());
+
+            if factor.repetition.is_none() && exception.is_none() {
+                return terms;
+            }
+
+            match <[Term; 1]>::try_from(terms) {
+                Ok([term]) if term.repetition.is_none() && term.exception.is_none() => {
+                    term.factor.primary
+                }
+                _ => Primary::Grouped(GroupedSequence::new(rhs.clone())),
+            }
+        }
+        Primary::Optional(seq) => Primary::Optional(OptionalSequence::new(inline(
+            seq.into_definitions_list(),
+            name,
+            rhs,
+        ))),
+        Primary::Repeated(seq) => Primary::Repeated(RepeatedSequence::new(inline(
+            seq.into_definitions_list(),
+            name,
+            rhs,
+        ))),
+        Primary::Grouped(seq) => Primary::Grouped(GroupedSequence::new(inline(
+            seq.into_definitions_list(),
+            name,
+            rhs,
+        ))),
+        primary => primary,
+    };
+
+    vec![Term { factor, exception }]
+}
+
+/// Returns true if the terms reference the rule, including in sequences.
+fn references(terms: &[Term], name: &str) -> bool {
+    let defs = DefinitionsList::from_iter([terms.iter().cloned().collect::<SingleDefinition>()]);
+    let mut found = false;
+
+    walk_primaries(&defs, &mut |primary| {
+        found |= matches!(primary, Primary::MetaIdentifier(id) if &**id == name);
+    });
+
+    found
+}
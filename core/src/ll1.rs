@@ -0,0 +1,245 @@
+//! LL(1) conflict detection.
+//!
+//! A grammar is LL(1) if, at every choice, a single lookahead terminal is
+//! enough to pick the way to go. Choices are the alternatives of a
+//! definitions list, and entering or skipping an optional or repeated
+//! sequence. The latter are seen as the alternatives `E | ` and `E, {E} | `.
+use std::fmt::Display;
+
+use crate::{
+    analysis::{Analysis, LookaheadSet},
+    prelude::*,
+    DefinitionsList, Primary, SingleDefinition,
+};
+
+/// One of the two alternatives involved in a conflict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alternative {
+    /// Index of the alternative in its definitions list.
+    ///
+    /// For optional and repeated sequences, 0 is entering the sequence and 1
+    /// is skipping it.
+    pub index: usize,
+    /// The alternative, pretty-printed.
+    pub text: String,
+}
+
+/// Two alternatives which cannot be told apart with one lookahead terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The rule in which the conflict occurs.
+    pub rule: String,
+    /// The nested sequence in which the conflict occurs, pretty-printed, or
+    /// None if it is between the alternatives of the rule itself.
+    pub context: Option<String>,
+    pub first: Alternative,
+    pub second: Alternative,
+    /// Terminals predicting both alternatives.
+    pub lookaheads: LookaheadSet,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<{}>", self.rule)?;
+
+        if let Some(context) = &self.context {
+            write!(f, " in {context}")?;
+        }
+
+        let lookaheads = self
+            .lookaheads
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(
+            f,
+            ": alternatives #{} ({}) and #{} ({}) are both predicted by {}",
+            self.first.index, self.first.text, self.second.index, self.second.text, lookaheads
+        )
+    }
+}
+
+/// Returns every LL(1) conflict of the syntax, the start symbol being the lhs
+/// of the first rule.
+pub fn check<S: ISyntax>(syntax: &S) -> Vec<Conflict> {
+    check_with(syntax, &Analysis::new(syntax))
+}
+
+/// Returns every LL(1) conflict of the syntax, using an existing analysis.
+pub fn check_with<S: ISyntax>(syntax: &S, analysis: &Analysis) -> Vec<Conflict> {
+    let mut checker = Checker {
+        analysis,
+        rule: String::default(),
+        conflicts: Vec::default(),
+    };
+
+    let mut seen = std::collections::HashSet::<String>::default();
+
+    for rule in syntax.as_ref() {
+        let lhs = rule.lhs().to_string();
+
+        if !seen.insert(lhs.clone()) {
+            continue;
+        }
+
+        let (Some(rhs), Some(follow)) = (analysis.rhs(&lhs), analysis.follow(&lhs)) else {
+            continue;
+        };
+
+        checker.rule = lhs;
+        checker.check_definitions_list(rhs, follow, None);
+    }
+
+    checker.conflicts
+}
+
+/// Returns true if the syntax is LL(1).
+pub fn is_ll1<S: ISyntax>(syntax: &S) -> bool {
+    check(syntax).is_empty()
+}
+
+struct Checker<'a> {
+    analysis: &'a Analysis,
+    rule: String,
+    conflicts: Vec<Conflict>,
+}
+
+impl Checker<'_> {
+    /// Returns the terminals predicting the alternative.
+    fn predict(&self, def: &SingleDefinition, trailer: &LookaheadSet) -> LookaheadSet {
+        let mut set = self.analysis.first_of_single_definition(def);
+
+        if self.analysis.is_nullable_single_definition(def) {
+            set.extend(trailer.iter().cloned());
+        }
+
+        set
+    }
+
+    fn push(
+        &mut self,
+        context: Option<&String>,
+        first: Alternative,
+        second: Alternative,
+        lookaheads: LookaheadSet,
+    ) {
+        if !lookaheads.is_empty() {
+            self.conflicts.push(Conflict {
+                rule: self.rule.clone(),
+                context: context.cloned(),
+                first,
+                second,
+                lookaheads,
+            })
+        }
+    }
+
+    fn check_definitions_list(
+        &mut self,
+        defs: &DefinitionsList,
+        trailer: &LookaheadSet,
+        context: Option<String>,
+    ) {
+        let predicts = defs
+            .iter()
+            .map(|def| self.predict(def, trailer))
+            .collect::<Vec<_>>();
+
+        for i in 0..defs.len() {
+            for j in (i + 1)..defs.len() {
+                self.push(
+                    context.as_ref(),
+                    Alternative {
+                        index: i,
+                        text: defs[i].to_string(),
+                    },
+                    Alternative {
+                        index: j,
+                        text: defs[j].to_string(),
+                    },
+                    predicts[i].intersection(&predicts[j]).cloned().collect(),
+                );
+            }
+        }
+
+        for def in defs.iter() {
+            self.check_single_definition(def, trailer);
+        }
+    }
+
+    fn check_single_definition(&mut self, def: &SingleDefinition, trailer: &LookaheadSet) {
+        let mut trailer = trailer.clone();
+
+        for term in def.iter().rev() {
+            let mut inner = trailer.clone();
+
+            if term.repetition.is_some_and(|n| n > 1) {
+                inner.extend(self.analysis.first_of_primary(&term.primary));
+            }
+
+            if term.repetition != Some(0) {
+                self.check_primary(&term.primary, &inner);
+            }
+
+            if self.analysis.is_nullable_term(term) {
+                trailer.extend(self.analysis.first_of_term(term));
+            } else {
+                trailer = self.analysis.first_of_term(term);
+            }
+        }
+    }
+
+    fn check_primary(&mut self, primary: &Primary, trailer: &LookaheadSet) {
+        let context = Some(primary.to_string());
+
+        match primary {
+            Primary::Grouped(seq) => self.check_definitions_list(seq, trailer, context),
+            Primary::Optional(seq) => {
+                self.check_skip(seq, trailer, context.as_ref());
+                self.check_definitions_list(seq, trailer, context)
+            }
+            Primary::Repeated(seq) => {
+                let seq = seq.as_ref();
+                self.check_skip(seq, trailer, context.as_ref());
+
+                // The sequence can be followed by another iteration of itself.
+                let mut trailer = trailer.clone();
+                trailer.extend(self.analysis.first_of_definitions_list(seq));
+                self.check_definitions_list(seq, &trailer, context)
+            }
+            Primary::MetaIdentifier(_)
+            | Primary::Literal(_)
+            | Primary::Special(_)
+            | Primary::Empty => {}
+        }
+    }
+
+    /// Checks the choice between entering or skipping an optional sequence.
+    fn check_skip(
+        &mut self,
+        seq: &DefinitionsList,
+        trailer: &LookaheadSet,
+        context: Option<&String>,
+    ) {
+        let mut enter = self.analysis.first_of_definitions_list(seq);
+
+        if self.analysis.is_nullable_definitions_list(seq) {
+            enter.extend(trailer.iter().cloned());
+        }
+
+        self.push(
+            context,
+            Alternative {
+                index: 0,
+                text: seq.to_string(),
+            },
+            Alternative {
+                index: 1,
+                text: String::default(),
+            },
+            enter.intersection(trailer).cloned().collect(),
+        );
+    }
+}
@@ -0,0 +1,668 @@
+//! Predictive parse table of an LL(1) syntax, and its stack-based driver.
+//!
+//! The syntax is compiled into plain productions, each optional, repeated
+//! and grouped sequence getting an anonymous non-terminal. The table maps a
+//! non-terminal and a lookahead terminal to the production to expand.
+//!
+//! The lookaheads are the ones of [crate::analysis], whose sets the table
+//! is built from: a literal and a terminal of the same name are distinct.
+//! The driver parses a stream of terminals, eg. the kinds of the tokens
+//! returned by a lexer, a token standing for a literal before a terminal of
+//! the same name.
+//!
+//! The table can be emitted as `const` data by the `ebnf!` macro, with
+//! `#![emit(ll1_table)]`.
+use std::{collections::HashMap, fmt::Display};
+
+use crate::{
+    analysis::{Analysis, Lookahead, LookaheadRef, LookaheadSet},
+    cst::Node,
+    prelude::*,
+    syntax::to_syntax,
+    visit::has_exception,
+    DefinitionsList, Primary, SyntaxRef, Term,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableSymbolRef<'a> {
+    NonTerminal(usize),
+    Literal(&'a str),
+    Terminal(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableSymbol {
+    NonTerminal(usize),
+    Literal(String),
+    Terminal(String),
+}
+
+impl TableSymbol {
+    pub fn as_ref(&self) -> TableSymbolRef<'_> {
+        match self {
+            TableSymbol::NonTerminal(id) => TableSymbolRef::NonTerminal(*id),
+            TableSymbol::Literal(value) => TableSymbolRef::Literal(value),
+            TableSymbol::Terminal(name) => TableSymbolRef::Terminal(name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProductionRef<'a> {
+    pub lhs: usize,
+    pub rhs: &'a [TableSymbolRef<'a>],
+}
+
+impl<'a> ProductionRef<'a> {
+    pub const fn new(lhs: usize, rhs: &'a [TableSymbolRef<'a>]) -> Self {
+        Self { lhs, rhs }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub lhs: usize,
+    pub rhs: Vec<TableSymbol>,
+}
+
+/// A cell of the table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryRef<'a> {
+    pub non_terminal: usize,
+    pub lookahead: LookaheadRef<'a>,
+    pub production: usize,
+}
+
+impl<'a> EntryRef<'a> {
+    pub const fn new(non_terminal: usize, lookahead: LookaheadRef<'a>, production: usize) -> Self {
+        Self {
+            non_terminal,
+            lookahead,
+            production,
+        }
+    }
+}
+
+/// A cell of the table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub non_terminal: usize,
+    pub lookahead: Lookahead,
+    pub production: usize,
+}
+
+/// Common interface of the owned and borrowed parse tables.
+///
+/// Non-terminal 0 is the start symbol, the lhs of the first rule.
+pub trait IParseTable {
+    /// Returns the name of the rule, None for the anonymous non-terminals.
+    fn name(&self, non_terminal: usize) -> Option<&str>;
+
+    /// Returns the production to expand on the lookahead.
+    fn lookup(&self, non_terminal: usize, lookahead: LookaheadRef) -> Option<usize>;
+
+    fn rhs(&self, production: usize) -> Vec<TableSymbolRef<'_>>;
+
+    /// Returns the lookaheads having an entry for the non-terminal.
+    fn expected(&self, non_terminal: usize) -> Vec<LookaheadRef<'_>>;
+
+    /// Parses a stream of terminals, starting from the first rule.
+    ///
+    /// The spans of the nodes are ranges of token indices. The anonymous
+    /// non-terminals do not get their own nodes.
+    fn parse<I>(&self, tokens: I) -> Result<Node, DriverError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        Self: Sized,
+    {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| token.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        drive(self, &tokens)
+    }
+}
+
+/// Parse table borrowing its data, as emitted by the `ebnf!` macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseTableRef<'a> {
+    pub non_terminals: &'a [Option<&'a str>],
+    pub productions: &'a [ProductionRef<'a>],
+    /// Sorted by non-terminal, then lookahead.
+    pub entries: &'a [EntryRef<'a>],
+}
+
+impl<'a> ParseTableRef<'a> {
+    pub const fn new(
+        non_terminals: &'a [Option<&'a str>],
+        productions: &'a [ProductionRef<'a>],
+        entries: &'a [EntryRef<'a>],
+    ) -> Self {
+        Self {
+            non_terminals,
+            productions,
+            entries,
+        }
+    }
+}
+
+impl IParseTable for ParseTableRef<'_> {
+    fn name(&self, non_terminal: usize) -> Option<&str> {
+        self.non_terminals.get(non_terminal).copied().flatten()
+    }
+
+    fn lookup(&self, non_terminal: usize, lookahead: LookaheadRef) -> Option<usize> {
+        self.entries
+            .binary_search_by(|entry| {
+                (entry.non_terminal, entry.lookahead).cmp(&(non_terminal, lookahead))
+            })
+            .ok()
+            .map(|index| self.entries[index].production)
+    }
+
+    fn rhs(&self, production: usize) -> Vec<TableSymbolRef<'_>> {
+        self.productions[production].rhs.to_vec()
+    }
+
+    fn expected(&self, non_terminal: usize) -> Vec<LookaheadRef<'_>> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.non_terminal == non_terminal)
+            .map(|entry| entry.lookahead)
+            .collect()
+    }
+}
+
+/// Owned parse table, built from a syntax.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseTable {
+    pub non_terminals: Vec<Option<String>>,
+    pub productions: Vec<Production>,
+    /// Sorted by non-terminal, then lookahead.
+    pub entries: Vec<Entry>,
+}
+
+impl ParseTable {
+    /// Builds the table of a syntax, which must be LL(1) and free of
+    /// exceptions.
+    pub fn build<S: ISyntax>(syntax: &S) -> Result<Self, TableError> {
+        let owned = to_syntax(syntax);
+
+        if let Some(rule) = owned.iter().find(|rule| has_exception(&rule.rhs)) {
+            return Err(TableError::Exception(rule.lhs.to_string()));
+        }
+
+        let analysis = Analysis::new(&owned);
+        let mut builder = Builder {
+            analysis: &analysis,
+            names: HashMap::default(),
+            rules: Vec::default(),
+            table: Self::default(),
+        };
+
+        for (lhs, _) in analysis.iter_rules() {
+            builder.add_non_terminal(Some(lhs), lhs);
+        }
+
+        for (id, (lhs, rhs)) in analysis.iter_rules().enumerate() {
+            let follow = analysis.follow(lhs).cloned().unwrap_or_default();
+
+            for def in rhs.iter() {
+                builder.add_production(id, def, None, &follow);
+            }
+        }
+
+        let Builder {
+            rules, mut table, ..
+        } = builder;
+
+        table.entries.sort_by(|a, b| {
+            (a.non_terminal, &a.lookahead, a.production).cmp(&(
+                b.non_terminal,
+                &b.lookahead,
+                b.production,
+            ))
+        });
+        table.entries.dedup();
+
+        let collisions = table
+            .entries
+            .chunk_by(|a, b| a.non_terminal == b.non_terminal && a.lookahead == b.lookahead)
+            .filter(|entries| entries.len() > 1)
+            .map(|entries| Collision {
+                non_terminal: entries[0].non_terminal,
+                rule: rules[entries[0].non_terminal].clone(),
+                lookahead: entries[0].lookahead.clone(),
+                productions: entries.iter().map(|entry| entry.production).collect(),
+            })
+            .collect::<Vec<_>>();
+
+        if !collisions.is_empty() {
+            return Err(TableError::Collisions(collisions));
+        }
+
+        Ok(table)
+    }
+}
+
+/// Compiles the rules into productions, each optional, repeated and grouped
+/// sequence getting an anonymous non-terminal, and predicts them from the
+/// FIRST and FOLLOW sets of the analysis.
+struct Builder<'a> {
+    analysis: &'a Analysis,
+    names: HashMap<&'a str, usize>,
+    /// The rule each non-terminal comes from.
+    rules: Vec<String>,
+    table: ParseTable,
+}
+
+impl<'a> Builder<'a> {
+    fn add_non_terminal(&mut self, name: Option<&'a str>, rule: &str) -> usize {
+        let id = self.table.non_terminals.len();
+
+        if let Some(name) = name {
+            self.names.insert(name, id);
+        }
+
+        self.table.non_terminals.push(name.map(str::to_string));
+        self.rules.push(rule.to_string());
+        id
+    }
+
+    /// Adds the production of the non-terminal deriving the terms, followed
+    /// by the non-terminal of a repeated sequence, predicted on the FIRST set
+    /// of the terms, and on the terminals following them if they are
+    /// nullable.
+    fn add_production(
+        &mut self,
+        lhs: usize,
+        terms: &[Term],
+        repeated: Option<usize>,
+        follow: &LookaheadSet,
+    ) {
+        let mut rhs = Vec::<TableSymbol>::default();
+
+        for (i, term) in terms.iter().enumerate() {
+            let trailer = self.trailer(&terms[i + 1..], follow);
+            rhs.extend(self.term(lhs, term, &trailer));
+        }
+
+        rhs.extend(repeated.map(TableSymbol::NonTerminal));
+
+        let production = self.table.productions.len();
+        self.table.productions.push(Production { lhs, rhs });
+
+        let lookaheads = self.trailer(terms, follow);
+        self.table
+            .entries
+            .extend(lookaheads.into_iter().map(|lookahead| Entry {
+                non_terminal: lhs,
+                lookahead,
+                production,
+            }));
+    }
+
+    /// Returns the terminals which can start the terms, or follow them if
+    /// they are nullable.
+    fn trailer(&self, terms: &[Term], follow: &LookaheadSet) -> LookaheadSet {
+        let mut set = self.analysis.first_of_terms(terms);
+
+        if terms
+            .iter()
+            .all(|term| self.analysis.is_nullable_term(term))
+        {
+            set.extend(follow.iter().cloned());
+        }
+
+        set
+    }
+
+    fn term(&mut self, lhs: usize, term: &Term, follow: &LookaheadSet) -> Vec<TableSymbol> {
+        let n = term.repetition.unwrap_or(1) as usize;
+        let mut follow = follow.clone();
+
+        // n * X, every X but the last is followed by another X.
+        if n > 1 {
+            follow.extend(self.analysis.first_of_primary(&term.primary));
+        }
+
+        self.primary(lhs, &term.primary, &follow)
+            .into_iter()
+            .flat_map(|symbol| vec![symbol; n])
+            .collect()
+    }
+
+    fn primary(
+        &mut self,
+        lhs: usize,
+        primary: &Primary,
+        follow: &LookaheadSet,
+    ) -> Option<TableSymbol> {
+        let rule = self.rules[lhs].clone();
+
+        let symbol = match primary {
+            Primary::Optional(seq) => {
+                let id = self.add_non_terminal(None, &rule);
+                self.add_definitions_list(id, seq, None, follow);
+                self.add_production(id, &[], None, follow);
+                TableSymbol::NonTerminal(id)
+            }
+            // {X} becomes R = X, R | ; the sequence being followed by
+            // another iteration.
+            Primary::Repeated(seq) => {
+                let id = self.add_non_terminal(None, &rule);
+                let mut inner = follow.clone();
+                inner.extend(self.analysis.first_of_definitions_list(seq.as_ref()));
+
+                self.add_definitions_list(id, seq.as_ref(), Some(id), &inner);
+                self.add_production(id, &[], None, follow);
+                TableSymbol::NonTerminal(id)
+            }
+            Primary::Grouped(seq) => {
+                let id = self.add_non_terminal(None, &rule);
+                self.add_definitions_list(id, seq, None, follow);
+                TableSymbol::NonTerminal(id)
+            }
+            Primary::MetaIdentifier(id) => match self.names.get(&**id) {
+                Some(&id) => TableSymbol::NonTerminal(id),
+                None => TableSymbol::Terminal(id.to_string()),
+            },
+            Primary::Literal(lit) => TableSymbol::Literal(lit.to_string()),
+            Primary::Special(seq) => TableSymbol::Terminal(seq.to_string()),
+            Primary::Empty => return None,
+        };
+
+        Some(symbol)
+    }
+
+    fn add_definitions_list(
+        &mut self,
+        lhs: usize,
+        defs: &DefinitionsList,
+        repeated: Option<usize>,
+        follow: &LookaheadSet,
+    ) {
+        for def in defs.iter() {
+            self.add_production(lhs, def, repeated, follow);
+        }
+    }
+}
+
+impl IParseTable for ParseTable {
+    fn name(&self, non_terminal: usize) -> Option<&str> {
+        self.non_terminals.get(non_terminal)?.as_deref()
+    }
+
+    fn lookup(&self, non_terminal: usize, lookahead: LookaheadRef) -> Option<usize> {
+        self.entries
+            .binary_search_by(|entry| {
+                (entry.non_terminal, entry.lookahead.borrow()).cmp(&(non_terminal, lookahead))
+            })
+            .ok()
+            .map(|index| self.entries[index].production)
+    }
+
+    fn rhs(&self, production: usize) -> Vec<TableSymbolRef<'_>> {
+        self.productions[production]
+            .rhs
+            .iter()
+            .map(TableSymbol::as_ref)
+            .collect()
+    }
+
+    fn expected(&self, non_terminal: usize) -> Vec<LookaheadRef<'_>> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.non_terminal == non_terminal)
+            .map(|entry| entry.lookahead.borrow())
+            .collect()
+    }
+}
+
+/// A syntax together with its parse table, as emitted by the `ebnf!` macro.
+#[derive(Debug, Clone, Copy)]
+pub struct Ll1Grammar<'a> {
+    pub syntax: SyntaxRef<'a>,
+    pub table: ParseTableRef<'a>,
+}
+
+impl<'a> Ll1Grammar<'a> {
+    pub const fn new(syntax: SyntaxRef<'a>, table: ParseTableRef<'a>) -> Self {
+        Self { syntax, table }
+    }
+}
+
+/// Productions of a non-terminal predicted on the same lookahead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub non_terminal: usize,
+    /// The rule the non-terminal comes from, the enclosing one for the
+    /// anonymous non-terminals.
+    pub rule: String,
+    pub lookahead: Lookahead,
+    pub productions: Vec<usize>,
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let productions = self
+            .productions
+            .iter()
+            .map(|production| format!("#{production}"))
+            .collect::<Vec<_>>();
+
+        write!(
+            f,
+            "<{}>: {} predicts productions {} of non-terminal #{}",
+            self.rule,
+            self.lookahead,
+            productions.join(", "),
+            self.non_terminal
+        )
+    }
+}
+
+/// An error raised while building a parse table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableError {
+    /// The rule contains an exception, which cannot be predicted with a
+    /// lookahead terminal.
+    Exception(String),
+    /// The syntax is not LL(1).
+    Collisions(Vec<Collision>),
+}
+
+impl Display for TableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::Exception(rule) => {
+                write!(
+                    f,
+                    "<{rule}> contains an exception, which LL(1) parse tables do not support"
+                )
+            }
+            TableError::Collisions(collisions) => {
+                write!(f, "the syntax is not LL(1)")?;
+
+                for collision in collisions {
+                    write!(f, "\n{collision}")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
+
+/// An error raised by the driver on an unexpected terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverError {
+    /// Index of the unexpected token.
+    pub position: usize,
+    /// None for the end of the input.
+    pub found: Option<String>,
+    /// The terminals which would have been accepted, None for the end of the
+    /// input.
+    pub expected: Vec<Option<String>>,
+}
+
+impl Display for DriverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |terminal: &Option<String>| match terminal {
+            Some(terminal) => format!("{terminal:?}"),
+            None => "end of input".to_string(),
+        };
+
+        let expected = self.expected.iter().map(show).collect::<Vec<_>>();
+
+        write!(
+            f,
+            "unexpected {} at token {}, expecting {}",
+            show(&self.found),
+            self.position,
+            expected.join(" or ")
+        )
+    }
+}
+
+impl std::error::Error for DriverError {}
+
+enum Frame<'t> {
+    Symbol(TableSymbolRef<'t>),
+    /// Closes the node of a non-terminal, once its rhs has been parsed.
+    Reduce(usize),
+}
+
+fn drive<T: IParseTable>(table: &T, tokens: &[String]) -> Result<Node, DriverError> {
+    let mut pos = 0;
+    let mut stack = vec![Frame::Symbol(TableSymbolRef::NonTerminal(0))];
+    // Children of the non-terminals being parsed, the outermost first.
+    let mut children = vec![Vec::<Node>::default()];
+
+    let error = |pos: usize, expected: Vec<Option<&str>>| DriverError {
+        position: pos,
+        found: tokens.get(pos).cloned(),
+        expected: expected
+            .into_iter()
+            .map(|terminal| terminal.map(str::to_string))
+            .collect(),
+    };
+
+    while let Some(frame) = stack.pop() {
+        let lookahead = tokens.get(pos).map(String::as_str);
+
+        match frame {
+            Frame::Symbol(TableSymbolRef::NonTerminal(id)) => {
+                let production = Lookahead::of_token(lookahead)
+                    .iter()
+                    .find_map(|lookahead| table.lookup(id, lookahead.borrow()));
+
+                let Some(production) = production else {
+                    let expected = table.expected(id);
+                    let expected = expected.into_iter().map(LookaheadRef::as_terminal);
+                    return Err(error(pos, expected.collect()));
+                };
+
+                stack.push(Frame::Reduce(id));
+                stack.extend(table.rhs(production).into_iter().rev().map(Frame::Symbol));
+                children.push(Vec::default());
+            }
+            Frame::Symbol(TableSymbolRef::Literal(value)) if lookahead == Some(value) => {
+                children
+                    .last_mut()
+                    .unwrap()
+                    .push(Node::literal(value, pos..pos + 1));
+                pos += 1;
+            }
+            Frame::Symbol(TableSymbolRef::Terminal(name)) if lookahead == Some(name) => {
+                children
+                    .last_mut()
+                    .unwrap()
+                    .push(Node::terminal(name, pos..pos + 1));
+                pos += 1;
+            }
+            Frame::Symbol(
+                TableSymbolRef::Literal(terminal) | TableSymbolRef::Terminal(terminal),
+            ) => {
+                return Err(error(pos, vec![Some(terminal)]));
+            }
+            Frame::Reduce(id) => {
+                let nodes = children.pop().unwrap();
+                let parent = children.last_mut().unwrap();
+
+                match table.name(id) {
+                    Some(name) => {
+                        let start = nodes.first().map_or(pos, |node| node.span.start);
+                        parent.push(Node::rule(name, start..pos, nodes));
+                    }
+                    None => parent.extend(nodes),
+                }
+            }
+        }
+    }
+
+    if pos < tokens.len() {
+        return Err(error(pos, vec![None]));
+    }
+
+    Ok(children.pop().unwrap().remove(0))
+}
+
+impl quote::ToTokens for TableSymbol {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        tokens.extend(match self {
+            TableSymbol::NonTerminal(id) => {
+                quote! { ::pb_ebnf::ll1_table::TableSymbolRef::NonTerminal(#id) }
+            }
+            TableSymbol::Literal(value) => {
+                quote! { ::pb_ebnf::ll1_table::TableSymbolRef::Literal(#value) }
+            }
+            TableSymbol::Terminal(name) => {
+                quote! { ::pb_ebnf::ll1_table::TableSymbolRef::Terminal(#name) }
+            }
+        })
+    }
+}
+
+impl quote::ToTokens for Production {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        let lhs = self.lhs;
+        let rhs = crate::into_slice(self.rhs.iter());
+        tokens.extend(quote! {
+            ::pb_ebnf::ll1_table::ProductionRef::new(#lhs, #rhs)
+        })
+    }
+}
+
+impl quote::ToTokens for Entry {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        let non_terminal = self.non_terminal;
+        let production = self.production;
+        let lookahead = &self.lookahead;
+        tokens.extend(quote! {
+            ::pb_ebnf::ll1_table::EntryRef::new(#non_terminal, #lookahead, #production)
+        })
+    }
+}
+
+impl quote::ToTokens for ParseTable {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        let non_terminals = crate::into_slice(self.non_terminals.iter().map(|name| match name {
+            Some(name) => quote! { Some(#name) },
+            None => quote! { None },
+        }));
+        let productions = crate::into_slice(self.productions.iter());
+        let entries = crate::into_slice(self.entries.iter());
+        tokens.extend(quote! {
+            ::pb_ebnf::ll1_table::ParseTableRef::new(#non_terminals, #productions, #entries)
+        })
+    }
+}
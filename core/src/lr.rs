@@ -0,0 +1,658 @@
+//! LR(1) and LALR(1) automata, built from the BNF lowering of a syntax.
+//!
+//! Left-recursive syntaxes are natural LR grammars, eg.
+//!
+//! <expr> = <expr>, "+", <term> | <term>;
+//!
+//! Terminals are the text of literals, and the names of undefined meta
+//! identifiers and special sequences, as in [crate::analysis]. The rules
+//! created by the lowering for optional, repeated and grouped sequences are
+//! displayed with their name, after the EBNF rule they come from, eg.
+//! `<expr__grp_0>` for the first one created in `<expr>`, see
+//! [BnfLowering].
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fmt::Display,
+};
+
+use crate::{
+    analysis::{Lookahead, LookaheadSet},
+    bnf::{BnfLowering, LoweringError},
+    cst::Node,
+    ll1_table::DriverError,
+    prelude::*,
+    syntax::to_syntax,
+};
+
+use pb_bnf::term::Term as BnfTerm;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LrSymbol {
+    NonTerminal(usize),
+    Literal(String),
+    Terminal(String),
+}
+
+impl LrSymbol {
+    /// Returns the lookahead matching the symbol, if it is a terminal.
+    pub fn as_lookahead(&self) -> Option<Lookahead> {
+        match self {
+            LrSymbol::NonTerminal(_) => None,
+            LrSymbol::Literal(value) => Some(Lookahead::Literal(value.clone())),
+            LrSymbol::Terminal(name) => Some(Lookahead::Terminal(name.clone())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonTerminal {
+    /// Name of the rule in the BNF lowering.
+    pub name: String,
+    /// The EBNF rule it comes from.
+    pub rule: String,
+    /// Name displayed to the user, the augmented start being `start'`.
+    pub display: String,
+    /// False for the rules created by the lowering, and the augmented start.
+    pub named: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub lhs: usize,
+    pub rhs: Vec<LrSymbol>,
+}
+
+/// A production matched up to the dot, and the lookaheads which may follow
+/// it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    pub production: usize,
+    pub dot: usize,
+    pub lookaheads: LookaheadSet,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct State {
+    /// The closure of the kernel, the kernel items first.
+    pub items: Vec<Item>,
+    pub transitions: BTreeMap<LrSymbol, usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Shift(usize),
+    Reduce(usize),
+    Accept,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    ShiftReduce,
+    ReduceReduce,
+}
+
+/// A cell of the ACTION table holding more than one action.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub state: usize,
+    pub lookahead: Lookahead,
+    pub kind: ConflictKind,
+    pub actions: Vec<Action>,
+    /// The EBNF rules of the items involved, in order and deduplicated.
+    pub rules: Vec<String>,
+    /// The items involved, pretty-printed.
+    pub items: Vec<String>,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            ConflictKind::ShiftReduce => "shift/reduce",
+            ConflictKind::ReduceReduce => "reduce/reduce",
+        };
+
+        write!(
+            f,
+            "{kind} conflict in state {} on {}: {}",
+            self.state,
+            self.lookahead,
+            self.items.join(" | ")
+        )
+    }
+}
+
+/// An LR automaton, with its ACTION and GOTO tables.
+///
+/// Non-terminal 0 is the augmented start, deriving the lhs of the first
+/// rule, and production 0 is its only production.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Automaton {
+    pub non_terminals: Vec<NonTerminal>,
+    pub productions: Vec<Production>,
+    pub states: Vec<State>,
+    /// Actions of each state on each lookahead, more than one being a
+    /// conflict.
+    pub action: Vec<BTreeMap<Lookahead, Vec<Action>>>,
+    pub goto: Vec<BTreeMap<usize, usize>>,
+}
+
+impl Automaton {
+    /// Builds the LALR(1) automaton, the LR(0) automaton whose items carry
+    /// the union of the lookaheads of the LR(1) items sharing their core.
+    ///
+    /// Fails if the syntax contains an exception, see [BnfLowering].
+    pub fn lalr1<S: ISyntax>(syntax: &S) -> Result<Self, LoweringError> {
+        Self::build(syntax, true)
+    }
+
+    /// Builds the canonical LR(1) automaton, which may have a lot more states
+    /// than the LALR(1) one but less conflicts.
+    pub fn lr1<S: ISyntax>(syntax: &S) -> Result<Self, LoweringError> {
+        Self::build(syntax, false)
+    }
+
+    fn build<S: ISyntax>(syntax: &S, merge: bool) -> Result<Self, LoweringError> {
+        let owned = to_syntax(syntax);
+
+        let Some(start) = owned.first().map(|rule| rule.lhs.to_string()) else {
+            return Ok(Self::default());
+        };
+
+        let lowering = BnfLowering::try_from(owned)?;
+        let mut automaton = Self::compile(&lowering, &start);
+
+        let sets = Sets::new(&automaton);
+        automaton.states = sets.states(merge);
+        automaton.fill_tables();
+        Ok(automaton)
+    }
+
+    fn compile(lowering: &BnfLowering, start: &str) -> Self {
+        let mut automaton = Self::default();
+        let mut names = HashMap::<String, usize>::default();
+
+        automaton.non_terminals.push(NonTerminal {
+            name: String::default(),
+            rule: start.to_string(),
+            display: format!("{start}'"),
+            named: false,
+        });
+
+        for rule in lowering.syntax.iter() {
+            let name = rule.lhs.to_string();
+
+            if names.contains_key(&name) {
+                continue;
+            }
+
+            let rule = lowering.origin(&name).to_string();
+
+            names.insert(name.clone(), automaton.non_terminals.len());
+            automaton.non_terminals.push(NonTerminal {
+                named: !lowering.origins.contains_key(&name),
+                display: name.clone(),
+                name,
+                rule,
+            });
+        }
+
+        automaton.productions.push(Production {
+            lhs: 0,
+            rhs: vec![LrSymbol::NonTerminal(names[start])],
+        });
+
+        for rule in lowering.syntax.iter() {
+            let lhs = names[&*rule.lhs];
+
+            for def in rule.rhs.iter() {
+                let rhs = def
+                    .iter()
+                    .map(|term| match term {
+                        BnfTerm::Symbol(symbol) => match names.get(&**symbol) {
+                            Some(&id) => LrSymbol::NonTerminal(id),
+                            None => LrSymbol::Terminal(symbol.to_string()),
+                        },
+                        BnfTerm::Literal(lit) => LrSymbol::Literal(lit.to_string()),
+                    })
+                    .collect();
+
+                automaton.productions.push(Production { lhs, rhs });
+            }
+        }
+
+        automaton
+    }
+
+    fn fill_tables(&mut self) {
+        for state in self.states.iter() {
+            let mut action = BTreeMap::<Lookahead, BTreeSet<Action>>::default();
+            let mut goto = BTreeMap::<usize, usize>::default();
+
+            for item in state.items.iter() {
+                let rhs = &self.productions[item.production].rhs;
+
+                match rhs.get(item.dot) {
+                    Some(LrSymbol::NonTerminal(id)) => {
+                        goto.insert(*id, state.transitions[&rhs[item.dot]]);
+                    }
+                    Some(symbol) => {
+                        let lookahead = symbol.as_lookahead().unwrap();
+                        let target = state.transitions[symbol];
+                        action
+                            .entry(lookahead)
+                            .or_default()
+                            .insert(Action::Shift(target));
+                    }
+                    None if item.production == 0 => {
+                        action
+                            .entry(Lookahead::End)
+                            .or_default()
+                            .insert(Action::Accept);
+                    }
+                    None => {
+                        for lookahead in item.lookaheads.iter() {
+                            action
+                                .entry(lookahead.clone())
+                                .or_default()
+                                .insert(Action::Reduce(item.production));
+                        }
+                    }
+                }
+            }
+
+            self.action.push(
+                action
+                    .into_iter()
+                    .map(|(lookahead, actions)| (lookahead, actions.into_iter().collect()))
+                    .collect(),
+            );
+            self.goto.push(goto);
+        }
+    }
+
+    /// Returns every cell of the ACTION table holding more than one action.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::<Conflict>::default();
+
+        for (index, cells) in self.action.iter().enumerate() {
+            for (lookahead, actions) in cells.iter().filter(|(_, actions)| actions.len() > 1) {
+                let kind = if actions.iter().any(|a| matches!(a, Action::Shift(_))) {
+                    ConflictKind::ShiftReduce
+                } else {
+                    ConflictKind::ReduceReduce
+                };
+
+                let involved = self.states[index]
+                    .items
+                    .iter()
+                    .filter(|item| {
+                        let rhs = &self.productions[item.production].rhs;
+
+                        match rhs.get(item.dot) {
+                            Some(symbol) => symbol.as_lookahead().as_ref() == Some(lookahead),
+                            None => item.lookaheads.contains(lookahead),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                let mut rules = Vec::<String>::default();
+
+                for item in involved.iter() {
+                    let rule = &self.non_terminals[self.productions[item.production].lhs].rule;
+
+                    if !rules.iter().any(|r| r == rule) {
+                        rules.push(rule.to_string());
+                    }
+                }
+
+                conflicts.push(Conflict {
+                    state: index,
+                    lookahead: lookahead.clone(),
+                    kind,
+                    actions: actions.clone(),
+                    rules,
+                    items: involved
+                        .iter()
+                        .map(|item| self.display_item(item))
+                        .collect(),
+                });
+            }
+        }
+
+        conflicts
+    }
+
+    pub fn is_conflict_free(&self) -> bool {
+        self.action
+            .iter()
+            .all(|cells| cells.values().all(|actions| actions.len() <= 1))
+    }
+
+    pub fn display_symbol(&self, symbol: &LrSymbol) -> String {
+        match symbol {
+            LrSymbol::NonTerminal(id) => format!("<{}>", self.non_terminals[*id].display),
+            LrSymbol::Literal(value) => format!("{value:?}"),
+            LrSymbol::Terminal(name) => format!("<{name}>"),
+        }
+    }
+
+    /// Pretty-prints an item, eg. `<expr> = <expr> • "+" <term>`.
+    pub fn display_item(&self, item: &Item) -> String {
+        let prod = &self.productions[item.production];
+        let mut text = format!("<{}> =", self.non_terminals[prod.lhs].display);
+
+        for (i, symbol) in prod.rhs.iter().enumerate() {
+            if i == item.dot {
+                text.push_str(" •");
+            }
+
+            text.push(' ');
+            text.push_str(&self.display_symbol(symbol));
+        }
+
+        if item.dot == prod.rhs.len() {
+            text.push_str(" •");
+        }
+
+        text
+    }
+
+    /// Returns the lookahead a token stands for in the state: the first of
+    /// [Lookahead::of_token] the state has actions on, or else the first one.
+    pub fn lookahead(&self, state: usize, token: Option<&str>) -> Lookahead {
+        let mut candidates = Lookahead::of_token(token);
+        let index = candidates
+            .iter()
+            .position(|lookahead| {
+                self.action
+                    .get(state)
+                    .is_some_and(|cells| cells.contains_key(lookahead))
+            })
+            .unwrap_or_default();
+
+        candidates.swap_remove(index)
+    }
+
+    /// Parses a stream of terminals.
+    ///
+    /// The spans of the nodes are ranges of token indices, and the rules
+    /// created by the lowering do not get their own nodes. Conflicts are
+    /// resolved by shifting, then by reducing the earliest production.
+    pub fn parse<I>(&self, tokens: I) -> Result<Node, DriverError>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let tokens = tokens
+            .into_iter()
+            .map(|token| token.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        let mut pos = 0;
+        let mut states = vec![0];
+        // Nodes of each symbol on the stack.
+        let mut nodes = Vec::<Vec<Node>>::default();
+
+        loop {
+            let state = *states.last().unwrap();
+            let lookahead = self.lookahead(state, tokens.get(pos).map(String::as_str));
+
+            let action = self
+                .action
+                .get(state)
+                .and_then(|cells| cells.get(&lookahead))
+                .and_then(|actions| actions.first());
+
+            match action {
+                Some(Action::Shift(target)) => {
+                    let token = &tokens[pos];
+
+                    let node = match lookahead {
+                        Lookahead::Literal(_) => Node::literal(token, pos..pos + 1),
+                        _ => Node::terminal(token, pos..pos + 1),
+                    };
+
+                    nodes.push(vec![node]);
+                    states.push(*target);
+                    pos += 1;
+                }
+                Some(&Action::Reduce(production)) => {
+                    let prod = &self.productions[production];
+                    let at = nodes.len() - prod.rhs.len();
+                    let children = nodes.drain(at..).flatten().collect::<Vec<_>>();
+                    states.truncate(states.len() - prod.rhs.len());
+
+                    let nt = &self.non_terminals[prod.lhs];
+
+                    if nt.named {
+                        let start = children.first().map_or(pos, |node| node.span.start);
+                        nodes.push(vec![Node::rule(&nt.name, start..pos, children)]);
+                    } else {
+                        nodes.push(children);
+                    }
+
+                    let top = *states.last().unwrap();
+                    states.push(self.goto[top][&prod.lhs]);
+                }
+                Some(Action::Accept) => return Ok(nodes.pop().unwrap().remove(0)),
+                None => {
+                    let expected = self
+                        .action
+                        .get(state)
+                        .into_iter()
+                        .flat_map(|cells| cells.keys())
+                        .map(|lookahead| lookahead.as_terminal().map(str::to_string))
+                        .collect();
+
+                    return Err(DriverError {
+                        position: pos,
+                        found: tokens.get(pos).cloned(),
+                        expected,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Kernel items, mapping each core to its lookaheads.
+type Kernel = BTreeMap<(usize, usize), LookaheadSet>;
+
+/// Nullable and FIRST sets of the non-terminals, used to compute closures.
+struct Sets<'a> {
+    automaton: &'a Automaton,
+    by_lhs: Vec<Vec<usize>>,
+    nullable: Vec<bool>,
+    first: Vec<LookaheadSet>,
+}
+
+impl<'a> Sets<'a> {
+    fn new(automaton: &'a Automaton) -> Self {
+        let len = automaton.non_terminals.len();
+        let mut by_lhs = vec![Vec::<usize>::default(); len];
+
+        for (index, prod) in automaton.productions.iter().enumerate() {
+            by_lhs[prod.lhs].push(index);
+        }
+
+        let mut sets = Self {
+            automaton,
+            by_lhs,
+            nullable: vec![false; len],
+            first: vec![LookaheadSet::default(); len],
+        };
+
+        loop {
+            let mut changed = false;
+
+            for prod in automaton.productions.iter() {
+                if !sets.nullable[prod.lhs] && sets.is_nullable(&prod.rhs) {
+                    sets.nullable[prod.lhs] = true;
+                    changed = true;
+                }
+
+                for lookahead in sets.first_of(&prod.rhs) {
+                    changed |= sets.first[prod.lhs].insert(lookahead);
+                }
+            }
+
+            if !changed {
+                return sets;
+            }
+        }
+    }
+
+    fn is_nullable(&self, symbols: &[LrSymbol]) -> bool {
+        symbols.iter().all(|symbol| match symbol {
+            LrSymbol::NonTerminal(id) => self.nullable[*id],
+            LrSymbol::Literal(_) | LrSymbol::Terminal(_) => false,
+        })
+    }
+
+    fn first_of(&self, symbols: &[LrSymbol]) -> LookaheadSet {
+        let mut set = LookaheadSet::default();
+
+        for symbol in symbols {
+            match symbol {
+                LrSymbol::NonTerminal(id) => {
+                    set.extend(self.first[*id].iter().cloned());
+
+                    if !self.nullable[*id] {
+                        break;
+                    }
+                }
+                symbol => {
+                    set.extend(symbol.as_lookahead());
+                    break;
+                }
+            }
+        }
+
+        set
+    }
+
+    fn closure(&self, kernel: &Kernel) -> Vec<Item> {
+        let mut items = kernel.clone();
+        let mut order = kernel.keys().copied().collect::<Vec<_>>();
+        let mut queue = order.iter().copied().collect::<VecDeque<_>>();
+
+        while let Some(core @ (production, dot)) = queue.pop_front() {
+            let rhs = &self.automaton.productions[production].rhs;
+
+            let Some(LrSymbol::NonTerminal(id)) = rhs.get(dot) else {
+                continue;
+            };
+
+            let rest = &rhs[dot + 1..];
+            let mut lookaheads = self.first_of(rest);
+
+            if self.is_nullable(rest) {
+                lookaheads.extend(items[&core].iter().cloned());
+            }
+
+            for &next in self.by_lhs[*id].iter() {
+                let added = !items.contains_key(&(next, 0));
+
+                if added {
+                    order.push((next, 0));
+                }
+
+                let entry = items.entry((next, 0)).or_default();
+                let len = entry.len();
+                entry.extend(lookaheads.iter().cloned());
+
+                if added || entry.len() > len {
+                    queue.push_back((next, 0));
+                }
+            }
+        }
+
+        order
+            .into_iter()
+            .map(|core| Item {
+                production: core.0,
+                dot: core.1,
+                lookaheads: items.remove(&core).unwrap(),
+            })
+            .collect()
+    }
+
+    /// Builds the states, merging the ones sharing the same cores if asked.
+    fn states(&self, merge: bool) -> Vec<State> {
+        let key = |kernel: &Kernel| {
+            kernel
+                .iter()
+                .map(|(core, lookaheads)| {
+                    let lookaheads = if merge {
+                        LookaheadSet::default()
+                    } else {
+                        lookaheads.clone()
+                    };
+                    (*core, lookaheads)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let start = Kernel::from([((0, 0), LookaheadSet::from([Lookahead::End]))]);
+        let mut kernels = vec![start.clone()];
+        let mut transitions = vec![BTreeMap::<LrSymbol, usize>::default()];
+        let mut index = HashMap::from([(key(&start), 0)]);
+        let mut queue = VecDeque::from([0]);
+
+        while let Some(state) = queue.pop_front() {
+            let mut gotos = BTreeMap::<LrSymbol, Kernel>::default();
+
+            for item in self.closure(&kernels[state]) {
+                let rhs = &self.automaton.productions[item.production].rhs;
+
+                if let Some(symbol) = rhs.get(item.dot) {
+                    gotos
+                        .entry(symbol.clone())
+                        .or_default()
+                        .entry((item.production, item.dot + 1))
+                        .or_default()
+                        .extend(item.lookaheads);
+                }
+            }
+
+            for (symbol, kernel) in gotos {
+                let target = match index.get(&key(&kernel)) {
+                    Some(&target) => {
+                        let mut changed = false;
+
+                        for (core, lookaheads) in kernel {
+                            let existing = kernels[target].get_mut(&core).unwrap();
+                            let len = existing.len();
+                            existing.extend(lookaheads);
+                            changed |= existing.len() > len;
+                        }
+
+                        if changed && !queue.contains(&target) {
+                            queue.push_back(target);
+                        }
+
+                        target
+                    }
+                    None => {
+                        index.insert(key(&kernel), kernels.len());
+                        kernels.push(kernel);
+                        transitions.push(BTreeMap::default());
+                        queue.push_back(kernels.len() - 1);
+                        kernels.len() - 1
+                    }
+                };
+
+                transitions[state].insert(symbol, target);
+            }
+        }
+
+        kernels
+            .iter()
+            .zip(transitions)
+            .map(|(kernel, transitions)| State {
+                items: self.closure(kernel),
+                transitions,
+            })
+            .collect()
+    }
+}
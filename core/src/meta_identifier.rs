@@ -83,7 +83,7 @@ impl syn::parse::Parse for MetaIdentifier {
         let sym = Symbol::from(fragments.into_iter().fold(
             String::default(),
             |mut acc, fragment| {
-                if acc.ends_with('-') {
+                if acc.is_empty() || acc.ends_with('-') {
                     acc.push_str(&fragment.into_string());
                 } else {
                     acc.push(' ');
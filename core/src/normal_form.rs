@@ -0,0 +1,297 @@
+//! Plain rules shared by the conversions to normal forms, see [crate::cnf]
+//! and [crate::gnf].
+//!
+//! Rules are referenced by index, the start one being the first, and the
+//! terminals are left to the conversion: BNF terminals for CNF, EBNF
+//! primaries for GNF.
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Sym<T> {
+    Rule(usize),
+    Terminal(T),
+}
+
+#[derive(Debug)]
+pub(crate) struct NormalRule<T> {
+    pub name: String,
+    /// The rule of the converted syntax this one comes from, None for the
+    /// rules added for a terminal.
+    pub origin: Option<String>,
+    pub defs: Vec<Vec<Sym<T>>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct NormalGrammar<T> {
+    pub rules: Vec<NormalRule<T>>,
+    /// Names used so far, including the ones of the removed rules.
+    pub names: HashSet<String>,
+}
+
+impl<T> Default for NormalGrammar<T> {
+    fn default() -> Self {
+        Self {
+            rules: Vec::default(),
+            names: HashSet::default(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> NormalGrammar<T> {
+    /// Adds a rule without definitions, and reserves its name.
+    pub fn add_rule(&mut self, name: String, origin: Option<String>) -> usize {
+        self.names.insert(name.clone());
+        self.rules.push(NormalRule {
+            name,
+            origin,
+            defs: Vec::default(),
+        });
+        self.rules.len() - 1
+    }
+
+    /// Adds a definition to a rule, unless it already has it.
+    pub fn add(&mut self, rule: usize, def: Vec<Sym<T>>) {
+        if !self.rules[rule].defs.contains(&def) {
+            self.rules[rule].defs.push(def);
+        }
+    }
+
+    /// Returns a name derived from base which is not used yet, and reserves
+    /// it.
+    pub fn fresh(&mut self, base: &str) -> String {
+        let name = (0..)
+            .map(|i| match i {
+                0 => base.to_string(),
+                i => format!("{base}_{i}"),
+            })
+            .find(|name| !self.names.contains(name))
+            .unwrap();
+
+        self.names.insert(name.clone());
+        name
+    }
+
+    /// Returns true if the rule is used on a rhs.
+    pub fn uses(&self, rule: usize) -> bool {
+        self.rules
+            .iter()
+            .flat_map(|r| r.defs.iter().flatten())
+            .any(|symbol| *symbol == Sym::Rule(rule))
+    }
+
+    /// Adds a new start rule if the start one is used on a rhs, so that it
+    /// can keep its empty definition.
+    pub fn isolate_start(&mut self) {
+        if !self.uses(0) {
+            return;
+        }
+
+        let name = self.fresh(&format!("{}_start", self.rules[0].name));
+        let origin = self.rules[0].origin.clone();
+
+        // Shifts every rule by one.
+        for symbol in self
+            .rules
+            .iter_mut()
+            .flat_map(|r| r.defs.iter_mut().flatten())
+        {
+            if let Sym::Rule(id) = symbol {
+                *id += 1;
+            }
+        }
+
+        self.rules.insert(
+            0,
+            NormalRule {
+                name,
+                origin,
+                defs: vec![vec![Sym::Rule(1)]],
+            },
+        );
+    }
+
+    /// Returns true for each rule deriving the empty string.
+    pub fn nullable(&self) -> Vec<bool> {
+        let mut nullable = vec![false; self.rules.len()];
+
+        loop {
+            let mut changed = false;
+
+            for (id, rule) in self.rules.iter().enumerate() {
+                let is_nullable = rule.defs.iter().any(|def| {
+                    def.iter().all(|symbol| match symbol {
+                        Sym::Rule(id) => nullable[*id],
+                        Sym::Terminal(_) => false,
+                    })
+                });
+
+                if is_nullable && !nullable[id] {
+                    nullable[id] = true;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return nullable;
+            }
+        }
+    }
+
+    /// Removes the empty definitions, adding the definitions without the
+    /// nullable rules. The start rule keeps its empty definition, if any.
+    pub fn remove_empty(&mut self) {
+        let nullable = self.nullable();
+
+        for (id, rule) in self.rules.iter_mut().enumerate() {
+            let mut expanded = Vec::<Vec<Sym<T>>>::default();
+
+            for def in rule.defs.iter() {
+                let mut variants = vec![Vec::<Sym<T>>::default()];
+
+                for symbol in def.iter() {
+                    let skippable = matches!(symbol, Sym::Rule(id) if nullable[*id]);
+
+                    variants = variants
+                        .into_iter()
+                        .flat_map(|variant| {
+                            let mut with = variant.clone();
+                            with.push(symbol.clone());
+
+                            if skippable {
+                                vec![with, variant]
+                            } else {
+                                vec![with]
+                            }
+                        })
+                        .collect();
+                }
+
+                for variant in variants {
+                    if !variant.is_empty() && !expanded.contains(&variant) {
+                        expanded.push(variant);
+                    }
+                }
+            }
+
+            if id == 0 && nullable[0] {
+                expanded.push(Vec::default());
+            }
+
+            rule.defs = expanded;
+        }
+    }
+
+    /// Replaces the definitions made of a single rule by the definitions of
+    /// that rule.
+    pub fn remove_units(&mut self) {
+        let mut defs = Vec::<Vec<Vec<Sym<T>>>>::default();
+
+        for id in 0..self.rules.len() {
+            let mut reached = vec![id];
+            let mut expanded = Vec::<Vec<Sym<T>>>::default();
+            let mut i = 0;
+
+            while i < reached.len() {
+                for def in self.rules[reached[i]].defs.iter() {
+                    match def.as_slice() {
+                        [Sym::Rule(unit)] => {
+                            if !reached.contains(unit) {
+                                reached.push(*unit);
+                            }
+                        }
+                        _ => {
+                            if !expanded.contains(def) {
+                                expanded.push(def.clone());
+                            }
+                        }
+                    }
+                }
+
+                i += 1;
+            }
+
+            defs.push(expanded);
+        }
+
+        for (rule, defs) in self.rules.iter_mut().zip(defs) {
+            rule.defs = defs;
+        }
+    }
+
+    /// Removes the definitions using rules which derive no terminal string,
+    /// and the rules which cannot be reached from the start one.
+    pub fn remove_useless(&mut self) {
+        let mut productive = vec![false; self.rules.len()];
+
+        loop {
+            let mut changed = false;
+
+            for (id, rule) in self.rules.iter().enumerate() {
+                let is_productive = rule.defs.iter().any(|def| {
+                    def.iter().all(|symbol| match symbol {
+                        Sym::Rule(id) => productive[*id],
+                        Sym::Terminal(_) => true,
+                    })
+                });
+
+                if is_productive && !productive[id] {
+                    productive[id] = true;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        for rule in self.rules.iter_mut() {
+            rule.defs.retain(|def| {
+                def.iter().all(|symbol| match symbol {
+                    Sym::Rule(id) => productive[*id],
+                    Sym::Terminal(_) => true,
+                })
+            });
+        }
+
+        let mut reachable = vec![0];
+        let mut i = 0;
+
+        while i < reachable.len() {
+            for symbol in self.rules[reachable[i]].defs.iter().flatten() {
+                if let Sym::Rule(id) = symbol {
+                    if !reachable.contains(id) {
+                        reachable.push(*id);
+                    }
+                }
+            }
+
+            i += 1;
+        }
+
+        // Keeps the order of the rules, and renumbers them.
+        let kept = (0..self.rules.len())
+            .filter(|id| *id == 0 || reachable.contains(id))
+            .collect::<Vec<_>>();
+        let renumber = kept
+            .iter()
+            .enumerate()
+            .map(|(new, old)| (*old, new))
+            .collect::<HashMap<_, _>>();
+
+        let rules = std::mem::take(&mut self.rules);
+        self.rules = rules
+            .into_iter()
+            .enumerate()
+            .filter(|(id, _)| renumber.contains_key(id))
+            .map(|(_, mut rule)| {
+                for symbol in rule.defs.iter_mut().flatten() {
+                    if let Sym::Rule(id) = symbol {
+                        *id = renumber[id];
+                    }
+                }
+                rule
+            })
+            .collect();
+    }
+}
@@ -40,6 +40,10 @@ impl<'a> IOptionalSequence for OptionalSequenceRef<'a> {
 pub struct OptionalSequence(DefinitionsList);
 
 impl OptionalSequence {
+    pub fn new(defs: DefinitionsList) -> Self {
+        Self(defs)
+    }
+
     pub fn is_parsable(input: &syn::parse::ParseStream) -> bool {
         input.peek(syn::token::Bracket)
     }
@@ -0,0 +1,441 @@
+//! Packrat parser, interpreting a syntax as a parsing expression grammar.
+//!
+//! Alternatives are an ordered choice, the first one matching wins, and
+//! optional and repeated sequences are greedy. An exception `X - Y` is read
+//! as a negative lookahead: X matches if Y does not match at the same
+//! position.
+//!
+//! The result of each rule at each position is memoised, so the parser runs
+//! in linear time, as long as the memo table stays under its cap. Rules
+//! depending on a left-recursive seed being grown are not memoised.
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ops::Range,
+};
+
+use crate::{
+    cst::{Node, NodeKind},
+    interpreter::Matcher,
+    parser::ParseError,
+    prelude::*,
+    syntax::to_syntax,
+    DefinitionsList, Factor, Primary, Syntax, Term,
+};
+
+/// Parses text with a syntax read as a PEG.
+///
+/// Literals are matched as is. Meta identifiers which are not defined by a
+/// rule, and special sequences, are matched with the [Matcher] registered
+/// under their name.
+pub struct Packrat {
+    syntax: Syntax,
+    /// Rules of the syntax defining each name, indexed by name.
+    names: HashMap<String, usize>,
+    rules: Vec<(String, Vec<usize>)>,
+    terminals: HashMap<String, Matcher>,
+    skip_whitespace: bool,
+    left_recursion: bool,
+    memo_limit: Option<usize>,
+}
+
+impl Packrat {
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let syntax = to_syntax(syntax);
+        let mut names = HashMap::<String, usize>::default();
+        let mut rules = Vec::<(String, Vec<usize>)>::default();
+
+        for (position, rule) in syntax.iter().enumerate() {
+            let index = *names.entry(rule.lhs.to_string()).or_insert_with(|| {
+                rules.push((rule.lhs.to_string(), Vec::default()));
+                rules.len() - 1
+            });
+
+            rules[index].1.push(position);
+        }
+
+        Self {
+            syntax,
+            names,
+            rules,
+            terminals: HashMap::default(),
+            skip_whitespace: false,
+            left_recursion: false,
+            memo_limit: None,
+        }
+    }
+
+    /// Registers the matcher of a terminal, either the name of an undefined
+    /// meta identifier or the text of a special sequence.
+    pub fn terminal<S, F>(mut self, name: S, matcher: F) -> Self
+    where
+        S: ToString,
+        F: Fn(&str) -> Option<usize> + 'static,
+    {
+        self.terminals.insert(name.to_string(), Box::new(matcher));
+        self
+    }
+
+    /// Skips whitespaces before each terminal and at the end of the input.
+    pub fn skip_whitespace(mut self, skip: bool) -> Self {
+        self.skip_whitespace = skip;
+        self
+    }
+
+    /// Grows the seed of left-recursive rules, as described by Warth et al.,
+    /// instead of failing on them.
+    ///
+    /// <sum> = <sum>, "+", <number> | <number>; then matches the longest sum,
+    /// associating to the left.
+    pub fn left_recursion(mut self, enabled: bool) -> Self {
+        self.left_recursion = enabled;
+        self
+    }
+
+    /// Caps the number of memoised results.
+    ///
+    /// Once reached, results are not memoised anymore: parsing stays correct,
+    /// but is no longer guaranteed to run in linear time.
+    ///
+    /// Only the memo table is capped. The matches it refers to are kept in an
+    /// arena until the end of the parse, including the ones of the
+    /// alternatives which failed later, so its size grows with the amount of
+    /// backtracking.
+    pub fn memo_limit(mut self, limit: usize) -> Self {
+        self.memo_limit = Some(limit);
+        self
+    }
+
+    /// Parses the whole input, starting from the first rule of the syntax.
+    pub fn parse(&self, input: &str) -> Result<Node, ParseError> {
+        match self.syntax.first() {
+            Some(rule) => self.parse_rule(&rule.lhs, input),
+            None => Err(ParseError::at(input, 0, "the syntax is empty")),
+        }
+    }
+
+    /// Parses the whole input, starting from the given rule.
+    pub fn parse_rule(&self, rule: &str, input: &str) -> Result<Node, ParseError> {
+        let Some(&index) = self.names.get(rule) else {
+            return Err(ParseError::at(input, 0, format!("<{rule}> is not defined")));
+        };
+
+        let mut run = Run {
+            packrat: self,
+            input,
+            matches: Vec::default(),
+            memo: HashMap::default(),
+            active: HashSet::default(),
+            recursive: HashSet::default(),
+            growing: 0,
+            furthest: 0,
+            expected: BTreeSet::default(),
+            quiet: 0,
+        };
+
+        let start = run.skip(0);
+
+        match run.rule(index, start) {
+            Some((end, id)) if run.skip(end) == input.len() => Ok(run.node(id)),
+            Some((end, _)) => {
+                let end = run.skip(end);
+                run.fail(end, "end of input".to_string());
+                Err(run.error())
+            }
+            None => Err(run.error()),
+        }
+    }
+}
+
+/// A match, stored once and referenced by index so that memoised results
+/// are shared instead of copied.
+struct Match {
+    kind: NodeKind,
+    span: Range<usize>,
+    children: Vec<usize>,
+}
+
+type Memo = Option<(usize, usize)>;
+
+struct Run<'r> {
+    packrat: &'r Packrat,
+    input: &'r str,
+    /// Every match made during the parse, never pruned, see
+    /// [Packrat::memo_limit].
+    matches: Vec<Match>,
+    /// End and match of each rule at each position, None if it fails.
+    memo: HashMap<(usize, usize), Memo>,
+    /// Rules being parsed at a position.
+    active: HashSet<(usize, usize)>,
+    /// Rules which have been called again at the same position.
+    recursive: HashSet<(usize, usize)>,
+    /// Number of seeds being grown.
+    growing: usize,
+    /// Furthest position at which a terminal failed, and what was expected.
+    furthest: usize,
+    expected: BTreeSet<String>,
+    /// Failures are not reported inside lookaheads.
+    quiet: usize,
+}
+
+impl Run<'_> {
+    fn skip(&self, pos: usize) -> usize {
+        if self.packrat.skip_whitespace {
+            let rest = &self.input[pos..];
+            pos + rest.len() - rest.trim_start().len()
+        } else {
+            pos
+        }
+    }
+
+    fn fail(&mut self, pos: usize, expected: String) {
+        if self.quiet > 0 || pos < self.furthest {
+            return;
+        }
+
+        if pos > self.furthest {
+            self.furthest = pos;
+            self.expected.clear();
+        }
+
+        self.expected.insert(expected);
+    }
+
+    fn error(&self) -> ParseError {
+        let expected = self.expected.iter().cloned().collect::<Vec<_>>();
+
+        ParseError::at(
+            self.input,
+            self.furthest,
+            format!("expecting {}", expected.join(" or ")),
+        )
+    }
+
+    /// Builds the tree of a match.
+    fn node(&self, id: usize) -> Node {
+        let m = &self.matches[id];
+
+        Node {
+            kind: m.kind.clone(),
+            span: m.span.clone(),
+            children: m.children.iter().map(|&child| self.node(child)).collect(),
+        }
+    }
+
+    fn add(&mut self, kind: NodeKind, span: Range<usize>, children: Vec<usize>) -> usize {
+        self.matches.push(Match {
+            kind,
+            span,
+            children,
+        });
+        self.matches.len() - 1
+    }
+
+    fn memoise(&mut self, key: (usize, usize), result: Memo) {
+        let full = self
+            .packrat
+            .memo_limit
+            .is_some_and(|limit| self.memo.len() >= limit);
+
+        if !full || self.memo.contains_key(&key) {
+            self.memo.insert(key, result);
+        }
+    }
+
+    fn rule(&mut self, index: usize, pos: usize) -> Memo {
+        let key = (index, pos);
+
+        if let Some(&result) = self.memo.get(&key) {
+            return result;
+        }
+
+        if self.active.contains(&key) {
+            self.recursive.insert(key);
+            return None;
+        }
+
+        self.active.insert(key);
+        let mut result = self.rule_body(index, pos);
+
+        // Grows the seed until the match does not get longer.
+        if self.recursive.remove(&key) && self.packrat.left_recursion {
+            self.growing += 1;
+
+            while let Some((end, _)) = result {
+                self.memo.insert(key, result);
+
+                match self.rule_body(index, pos) {
+                    Some(grown) if grown.0 > end => result = Some(grown),
+                    _ => break,
+                }
+            }
+
+            self.memo.remove(&key);
+            self.growing -= 1;
+        }
+
+        self.active.remove(&key);
+
+        // Results depending on a seed being grown are not final.
+        if self.growing == 0 && self.recursive.is_empty() {
+            self.memoise(key, result);
+        }
+
+        result
+    }
+
+    fn rule_body(&mut self, index: usize, pos: usize) -> Memo {
+        let packrat = self.packrat;
+        let (name, rhs) = &packrat.rules[index];
+
+        for &rule in rhs.iter() {
+            if let Some((end, children)) = self.definitions_list(&packrat.syntax[rule].rhs, pos) {
+                // Skipped whitespaces are not part of the rule.
+                let start = children
+                    .first()
+                    .map_or(pos, |&child| self.matches[child].span.start);
+
+                let id = self.add(NodeKind::Rule(name.clone()), start..end, children);
+                return Some((end, id));
+            }
+        }
+
+        None
+    }
+
+    fn definitions_list(
+        &mut self,
+        defs: &DefinitionsList,
+        pos: usize,
+    ) -> Option<(usize, Vec<usize>)> {
+        defs.iter().find_map(|def| self.terms(def, pos))
+    }
+
+    fn terms(&mut self, terms: &[Term], pos: usize) -> Option<(usize, Vec<usize>)> {
+        let mut end = pos;
+        let mut children = Vec::<usize>::default();
+
+        for term in terms {
+            end = self.term(term, end, &mut children)?;
+        }
+
+        Some((end, children))
+    }
+
+    fn term(&mut self, term: &Term, pos: usize, children: &mut Vec<usize>) -> Option<usize> {
+        if let Some(exception) = &term.exception {
+            self.quiet += 1;
+            let excepted = self.factor(exception, pos, &mut Vec::default());
+            self.quiet -= 1;
+
+            if excepted.is_some() {
+                return None;
+            }
+        }
+
+        self.factor(&term.factor, pos, children)
+    }
+
+    fn factor(&mut self, factor: &Factor, pos: usize, children: &mut Vec<usize>) -> Option<usize> {
+        let mut end = pos;
+
+        for _ in 0..factor.repetition.unwrap_or(1) {
+            end = self.primary(&factor.primary, end, children)?;
+        }
+
+        Some(end)
+    }
+
+    fn primary(
+        &mut self,
+        primary: &Primary,
+        pos: usize,
+        children: &mut Vec<usize>,
+    ) -> Option<usize> {
+        match primary {
+            Primary::Optional(seq) => match self.definitions_list(seq, pos) {
+                Some((end, matched)) => {
+                    children.extend(matched);
+                    Some(end)
+                }
+                None => Some(pos),
+            },
+            Primary::Repeated(seq) => {
+                let mut end = pos;
+
+                // An iteration matching nothing would loop forever.
+                while let Some((next, matched)) = self.definitions_list(seq.as_ref(), end) {
+                    if next == end {
+                        break;
+                    }
+
+                    children.extend(matched);
+                    end = next;
+                }
+
+                Some(end)
+            }
+            Primary::Grouped(seq) => {
+                let (end, matched) = self.definitions_list(seq, pos)?;
+                children.extend(matched);
+                Some(end)
+            }
+            Primary::MetaIdentifier(id) => match self.packrat.names.get(&**id) {
+                Some(&index) => {
+                    let (end, matched) = self.rule(index, pos)?;
+                    children.push(matched);
+                    Some(end)
+                }
+                None => self.terminal(id, format!("<{}>", &**id), pos, children),
+            },
+            Primary::Literal(lit) => {
+                let pos = self.skip(pos);
+
+                if self.input[pos..].starts_with(&**lit) {
+                    let end = pos + lit.len();
+                    children.push(self.add(
+                        NodeKind::Literal(lit.to_string()),
+                        pos..end,
+                        Vec::default(),
+                    ));
+                    Some(end)
+                } else {
+                    self.fail(pos, format!("{:?}", &**lit));
+                    None
+                }
+            }
+            Primary::Special(seq) => self.terminal(seq, format!("? {} ?", &**seq), pos, children),
+            Primary::Empty => Some(pos),
+        }
+    }
+
+    fn terminal(
+        &mut self,
+        name: &str,
+        expected: String,
+        pos: usize,
+        children: &mut Vec<usize>,
+    ) -> Option<usize> {
+        let pos = self.skip(pos);
+
+        let matched = self
+            .packrat
+            .terminals
+            .get(name)
+            .and_then(|matcher| matcher(&self.input[pos..]));
+
+        match matched {
+            Some(len) => {
+                children.push(self.add(
+                    NodeKind::Terminal(name.to_string()),
+                    pos..pos + len,
+                    Vec::default(),
+                ));
+                Some(pos + len)
+            }
+            None => {
+                self.fail(pos, expected);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! Runtime parser for grammars written in ISO 14977 EBNF.
+//!
+//! Unlike the [syn::parse::Parse] implementations, which require the grammar
+//! to be made of valid Rust tokens, this parser reads plain text, for example
+//! a grammar loaded from a `.ebnf` file.
+//!
+//! Supported notation:
+//! - comments `(* ... *)`, which may be nested,
+//! - terminal strings quoted with either `'` or `"`,
+//! - special sequences `? ... ?`,
+//! - meta identifiers either bare (`digit excluding zero`) or within angle brackets (`<Step Opt>`),
+//! - the alternate representations `/` and `!` for `|`, `(/ /)` for `[ ]`, `(: :)` for `{ }`,
+//! - both `;` and `.` as rule terminators.
+use std::fmt::Display;
+
+use crate::{
+    DefinitionsList, Exception, Factor, GroupedSequence, Literal, MetaIdentifier, OptionalSequence,
+    Primary, RepeatedSequence, Rule, SingleDefinition, SpecialSequence, Syntax, Term,
+};
+
+/// An error raised while parsing an EBNF text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    /// Column of the error, starting at 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new<S: ToString>(line: usize, column: usize, message: S) -> Self {
+        Self {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Creates an error located at a byte offset of the input.
+    pub(crate) fn at<S: ToString>(input: &str, offset: usize, message: S) -> Self {
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit('\n')
+            .next()
+            .map_or(0, |line| line.chars().count())
+            + 1;
+
+        Self::new(line, column, message)
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses a whole syntax, that is a sequence of rules.
+pub fn parse_syntax(input: &str) -> Result<Syntax, ParseError> {
+    let mut parser = Parser::new(input)?;
+    let mut syntax = Syntax::default();
+
+    while !parser.peek_is(&TokenKind::Eof) {
+        syntax.push(parser.parse_rule()?);
+    }
+
+    Ok(syntax)
+}
+
+/// Parses a single rule.
+pub fn parse_rule(input: &str) -> Result<Rule, ParseError> {
+    let mut parser = Parser::new(input)?;
+    let rule = parser.parse_rule()?;
+    parser.expect(TokenKind::Eof, "expecting the end of the input")?;
+    Ok(rule)
+}
+
+/// Parses a definitions list, that is the right-hand side of a rule.
+pub fn parse_definitions_list(input: &str) -> Result<DefinitionsList, ParseError> {
+    let mut parser = Parser::new(input)?;
+    let defs = parser.parse_definitions_list()?;
+    parser.expect(TokenKind::Eof, "expecting the end of the input")?;
+    Ok(defs)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    MetaIdentifier(String),
+    Terminal(String),
+    Special(String),
+    Integer(u32),
+    Defining,
+    Separator,
+    Concatenate,
+    Except,
+    Repetition,
+    Terminator,
+    StartOption,
+    EndOption,
+    StartRepeat,
+    EndRepeat,
+    StartGroup,
+    EndGroup,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn new(input: &str) -> Self {
+        Self {
+            chars: input.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek2(&self) -> Option<char> {
+        self.chars.get(self.pos + 1).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.pos += 1;
+
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(ch)
+    }
+
+    fn error<S: ToString>(&self, message: S) -> ParseError {
+        ParseError::new(self.line, self.column, message)
+    }
+
+    /// Skips whitespaces and comments.
+    fn skip_gaps(&mut self) -> Result<(), ParseError> {
+        loop {
+            match (self.peek(), self.peek2()) {
+                (Some(ch), _) if ch.is_whitespace() => {
+                    self.bump();
+                }
+                (Some('('), Some('*')) => self.skip_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_comment(&mut self) -> Result<(), ParseError> {
+        let (line, column) = (self.line, self.column);
+        let mut depth = 0;
+
+        loop {
+            match (self.peek(), self.peek2()) {
+                (Some('('), Some('*')) => {
+                    self.bump();
+                    self.bump();
+                    depth += 1;
+                }
+                (Some('*'), Some(')')) => {
+                    self.bump();
+                    self.bump();
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                (Some(_), _) => {
+                    self.bump();
+                }
+                (None, _) => return Err(ParseError::new(line, column, "unterminated comment")),
+            }
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, ParseError> {
+        let mut tokens = Vec::<Token>::default();
+
+        loop {
+            self.skip_gaps()?;
+
+            let (line, column) = (self.line, self.column);
+            let kind = self.next_kind()?;
+            let eof = kind == TokenKind::Eof;
+
+            tokens.push(Token { kind, line, column });
+
+            if eof {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn next_kind(&mut self) -> Result<TokenKind, ParseError> {
+        let Some(ch) = self.peek() else {
+            return Ok(TokenKind::Eof);
+        };
+
+        let kind = match (ch, self.peek2()) {
+            ('(', Some('/')) => {
+                self.bump();
+                TokenKind::StartOption
+            }
+            ('(', Some(':')) => {
+                self.bump();
+                TokenKind::StartRepeat
+            }
+            ('/', Some(')')) => {
+                self.bump();
+                TokenKind::EndOption
+            }
+            (':', Some(')')) => {
+                self.bump();
+                TokenKind::EndRepeat
+            }
+            ('(', _) => TokenKind::StartGroup,
+            (')', _) => TokenKind::EndGroup,
+            ('[', _) => TokenKind::StartOption,
+            (']', _) => TokenKind::EndOption,
+            ('{', _) => TokenKind::StartRepeat,
+            ('}', _) => TokenKind::EndRepeat,
+            ('|' | '/' | '!', _) => TokenKind::Separator,
+            ('=', _) => TokenKind::Defining,
+            (',', _) => TokenKind::Concatenate,
+            ('-', _) => TokenKind::Except,
+            ('*', _) => TokenKind::Repetition,
+            (';' | '.', _) => TokenKind::Terminator,
+            ('\'' | '"', _) => return self.terminal_string(ch),
+            ('?', _) => return self.special_sequence(),
+            ('<', _) => return self.angled_meta_identifier(),
+            (ch, _) if ch.is_ascii_digit() => return self.integer(),
+            (ch, _) if ch.is_alphabetic() => return Ok(self.meta_identifier()),
+            (ch, _) => return Err(self.error(format!("unexpected character {ch:?}"))),
+        };
+
+        self.bump();
+        Ok(kind)
+    }
+
+    fn terminal_string(&mut self, quote: char) -> Result<TokenKind, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+
+        let mut value = String::default();
+
+        loop {
+            match self.bump() {
+                Some(ch) if ch == quote => break,
+                Some(ch) => value.push(ch),
+                None => {
+                    return Err(ParseError::new(
+                        line,
+                        column,
+                        "unterminated terminal string",
+                    ))
+                }
+            }
+        }
+
+        if value.is_empty() {
+            return Err(ParseError::new(line, column, "empty terminal string"));
+        }
+
+        Ok(TokenKind::Terminal(value))
+    }
+
+    fn special_sequence(&mut self) -> Result<TokenKind, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+
+        let mut value = String::default();
+
+        loop {
+            match self.bump() {
+                Some('?') => break,
+                Some(ch) => value.push(ch),
+                None => {
+                    return Err(ParseError::new(
+                        line,
+                        column,
+                        "unterminated special sequence",
+                    ))
+                }
+            }
+        }
+
+        Ok(TokenKind::Special(value.trim().to_string()))
+    }
+
+    fn angled_meta_identifier(&mut self) -> Result<TokenKind, ParseError> {
+        let (line, column) = (self.line, self.column);
+        self.bump();
+
+        let mut value = String::default();
+
+        loop {
+            match self.bump() {
+                Some('>') => break,
+                Some(ch) => value.push(ch),
+                None => {
+                    return Err(ParseError::new(
+                        line,
+                        column,
+                        "unterminated meta identifier, expecting a >",
+                    ))
+                }
+            }
+        }
+
+        let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if value.is_empty() {
+            return Err(ParseError::new(line, column, "empty meta identifier"));
+        }
+
+        Ok(TokenKind::MetaIdentifier(value))
+    }
+
+    fn integer(&mut self) -> Result<TokenKind, ParseError> {
+        let (line, column) = (self.line, self.column);
+        let mut value = String::default();
+
+        while let Some(ch) = self.peek().filter(char::is_ascii_digit) {
+            value.push(ch);
+            self.bump();
+        }
+
+        value
+            .parse()
+            .map(TokenKind::Integer)
+            .map_err(|_| ParseError::new(line, column, "invalid integer"))
+    }
+
+    /// Reads a bare meta identifier.
+    ///
+    /// Gaps between the characters are allowed, the resulting fragments are
+    /// joined by a single space.
+    fn meta_identifier(&mut self) -> TokenKind {
+        let mut fragments = Vec::<String>::default();
+
+        loop {
+            let mut fragment = String::default();
+
+            while let Some(ch) = self.peek().filter(|ch| ch.is_alphanumeric() || *ch == '_') {
+                fragment.push(ch);
+                self.bump();
+            }
+
+            fragments.push(fragment);
+
+            let save = (self.pos, self.line, self.column);
+
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.bump();
+            }
+
+            if !self.peek().is_some_and(char::is_alphanumeric) {
+                (self.pos, self.line, self.column) = save;
+                break;
+            }
+        }
+
+        TokenKind::MetaIdentifier(fragments.join(" "))
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            tokens: Lexer::new(input).tokenize()?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        // The token list always ends with Eof, which is never consumed.
+        &self.tokens[self.pos.min(self.tokens.len() - 1)]
+    }
+
+    fn peek_is(&self, kind: &TokenKind) -> bool {
+        &self.peek().kind == kind
+    }
+
+    fn bump(&mut self) -> Token {
+        let token = self.peek().clone();
+
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+
+        token
+    }
+
+    fn error<S: ToString>(&self, message: S) -> ParseError {
+        let token = self.peek();
+        ParseError::new(token.line, token.column, message)
+    }
+
+    fn expect(&mut self, kind: TokenKind, message: &str) -> Result<(), ParseError> {
+        if self.peek_is(&kind) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn parse_rule(&mut self) -> Result<Rule, ParseError> {
+        let lhs = match self.peek().kind.clone() {
+            TokenKind::MetaIdentifier(id) => {
+                self.bump();
+                MetaIdentifier::new(id)
+            }
+            _ => return Err(self.error("expecting a meta identifier")),
+        };
+
+        self.expect(TokenKind::Defining, "expecting a = after rule's lhs")?;
+        let rhs = self.parse_definitions_list()?;
+        self.expect(TokenKind::Terminator, "expecting a ; or a .")?;
+
+        Ok(Rule::new(lhs, rhs))
+    }
+
+    fn parse_definitions_list(&mut self) -> Result<DefinitionsList, ParseError> {
+        let mut list = vec![self.parse_single_definition()?];
+
+        while self.peek_is(&TokenKind::Separator) {
+            self.bump();
+            list.push(self.parse_single_definition()?);
+        }
+
+        Ok(list.into_iter().collect())
+    }
+
+    fn parse_single_definition(&mut self) -> Result<SingleDefinition, ParseError> {
+        let mut list = vec![self.parse_term()?];
+
+        while self.peek_is(&TokenKind::Concatenate) {
+            self.bump();
+            list.push(self.parse_term()?);
+        }
+
+        Ok(list.into_iter().collect())
+    }
+
+    fn parse_term(&mut self) -> Result<Term, ParseError> {
+        let factor = self.parse_factor()?;
+
+        let exception = if self.peek_is(&TokenKind::Except) {
+            self.bump();
+            Some(Exception::new(self.parse_factor()?))
+        } else {
+            None
+        };
+
+        Ok(Term { factor, exception })
+    }
+
+    fn parse_factor(&mut self) -> Result<Factor, ParseError> {
+        let repetition = if let TokenKind::Integer(rep) = self.peek().kind {
+            self.bump();
+            self.expect(
+                TokenKind::Repetition,
+                "expecting a * after the repetition count",
+            )?;
+            Some(rep)
+        } else {
+            None
+        };
+
+        let primary = self.parse_primary()?;
+
+        Ok(Factor {
+            primary,
+            repetition,
+        })
+    }
+
+    fn parse_primary(&mut self) -> Result<Primary, ParseError> {
+        let primary = match self.peek().kind.clone() {
+            TokenKind::StartOption => {
+                self.bump();
+                let defs = self.parse_definitions_list()?;
+                self.expect(TokenKind::EndOption, "expecting a ] or a /)")?;
+                Primary::Optional(OptionalSequence::new(defs))
+            }
+            TokenKind::StartRepeat => {
+                self.bump();
+                let defs = self.parse_definitions_list()?;
+                self.expect(TokenKind::EndRepeat, "expecting a } or a :)")?;
+                Primary::Repeated(RepeatedSequence::new(defs))
+            }
+            TokenKind::StartGroup => {
+                self.bump();
+                let defs = self.parse_definitions_list()?;
+                self.expect(TokenKind::EndGroup, "expecting a )")?;
+                Primary::Grouped(GroupedSequence::new(defs))
+            }
+            TokenKind::MetaIdentifier(id) => {
+                self.bump();
+                Primary::MetaIdentifier(MetaIdentifier::new(id))
+            }
+            TokenKind::Terminal(value) => {
+                self.bump();
+                Primary::Literal(Literal::new(value))
+            }
+            TokenKind::Special(value) => {
+                self.bump();
+                Primary::Special(SpecialSequence::new(value))
+            }
+            _ => Primary::Empty,
+        };
+
+        Ok(primary)
+    }
+}
@@ -3,11 +3,13 @@ use std::iter::Once;
 use pb_bnf::{prelude::IterSymbols, symbol::Symbol};
 
 use crate::{
+    definitions_list::DefinitionsList,
     grouped::{GroupedSequence, GroupedSequenceRef, IGroupedSequence},
     literal::{ILiteral, Literal, LiteralRef},
     meta_identifier::{IMetaIdentifier, MetaIdentifier, MetaIdentifierRef},
     optional::{IOptionalSequence, OptionalSequence, OptionalSequenceRef},
     repeated::{IRepeatedSequence, RepeatedSequence, RepeatedSequenceRef},
+    special::{ISpecialSequence, SpecialSequence, SpecialSequenceRef},
     IntoTerm, Term,
 };
 
@@ -30,6 +32,7 @@ pub enum PrimaryKind {
     GroupedSequence,
     MetaId,
     Literal,
+    SpecialSequence,
     Empty,
 }
 
@@ -39,12 +42,14 @@ pub trait IPrimary {
     type GroupedSequence: IGroupedSequence;
     type MetaIdentifier: IMetaIdentifier;
     type Literal: ILiteral;
+    type SpecialSequence: ISpecialSequence;
 
     fn try_as_optional(&self) -> Option<&Self::OptionalSequence>;
     fn try_as_repeated(&self) -> Option<&Self::RepeatedSequence>;
     fn try_as_grouped(&self) -> Option<&Self::GroupedSequence>;
     fn try_as_meta_identifier(&self) -> Option<&Self::MetaIdentifier>;
     fn try_as_literal(&self) -> Option<&Self::Literal>;
+    fn try_as_special(&self) -> Option<&Self::SpecialSequence>;
 
     fn kind(&self) -> PrimaryKind;
 
@@ -58,6 +63,7 @@ pub enum PrimaryRef<'a> {
     Grouped(GroupedSequenceRef<'a>),
     MetaIdentifier(MetaIdentifierRef<'a>),
     Literal(LiteralRef<'a>),
+    Special(SpecialSequenceRef<'a>),
     Empty,
 }
 
@@ -67,6 +73,7 @@ impl<'a> IPrimary for PrimaryRef<'a> {
     type GroupedSequence = GroupedSequenceRef<'a>;
     type MetaIdentifier = MetaIdentifierRef<'a>;
     type Literal = LiteralRef<'a>;
+    type SpecialSequence = SpecialSequenceRef<'a>;
 
     fn try_as_optional(&self) -> Option<&Self::OptionalSequence> {
         if let Self::Optional(seq) = &self {
@@ -108,6 +115,14 @@ impl<'a> IPrimary for PrimaryRef<'a> {
         }
     }
 
+    fn try_as_special(&self) -> Option<&Self::SpecialSequence> {
+        if let Self::Special(seq) = &self {
+            Some(seq)
+        } else {
+            None
+        }
+    }
+
     fn kind(&self) -> PrimaryKind {
         match self {
             PrimaryRef::Optional(_) => PrimaryKind::OptionalSequence,
@@ -115,6 +130,7 @@ impl<'a> IPrimary for PrimaryRef<'a> {
             PrimaryRef::Grouped(_) => PrimaryKind::GroupedSequence,
             PrimaryRef::MetaIdentifier(_) => PrimaryKind::MetaId,
             PrimaryRef::Literal(_) => PrimaryKind::Literal,
+            PrimaryRef::Special(_) => PrimaryKind::SpecialSequence,
             PrimaryRef::Empty => PrimaryKind::Empty,
         }
     }
@@ -128,6 +144,7 @@ impl<'a> IPrimary for PrimaryRef<'a> {
                 Primary::MetaIdentifier(IMetaIdentifier::to_owned(id))
             }
             PrimaryRef::Literal(lit) => Primary::Literal(ILiteral::to_owned(lit)),
+            PrimaryRef::Special(seq) => Primary::Special(ISpecialSequence::to_owned(seq)),
             PrimaryRef::Empty => Primary::Empty,
         }
     }
@@ -160,9 +177,22 @@ pub enum Primary {
     Grouped(GroupedSequence),
     MetaIdentifier(MetaIdentifier),
     Literal(Literal),
+    Special(SpecialSequence),
     Empty,
 }
 
+impl Primary {
+    /// Returns the inner definitions list of an optional, repeated or grouped sequence.
+    pub fn try_as_definitions_list(&self) -> Option<&DefinitionsList> {
+        match self {
+            Self::Optional(seq) => Some(seq),
+            Self::Repeated(seq) => Some(seq.as_ref()),
+            Self::Grouped(seq) => Some(seq),
+            _ => None,
+        }
+    }
+}
+
 impl IntoTerm for Primary {
     type Term = Term;
 
@@ -190,6 +220,7 @@ impl<'a> IterSymbols<'a> for Primary {
                 PrimarySymbolIterator::from(std::iter::once(meta.as_symbol()))
             }
             Primary::Literal(_) => todo!(),
+            Primary::Special(seq) => PrimarySymbolIterator::from(std::iter::once(seq.as_symbol())),
             Primary::Empty => todo!(),
         }
     }
@@ -201,6 +232,7 @@ impl IPrimary for Primary {
     type GroupedSequence = GroupedSequence;
     type MetaIdentifier = MetaIdentifier;
     type Literal = Literal;
+    type SpecialSequence = SpecialSequence;
 
     fn try_as_optional(&self) -> Option<&Self::OptionalSequence> {
         if let Self::Optional(seq) = &self {
@@ -242,6 +274,14 @@ impl IPrimary for Primary {
         }
     }
 
+    fn try_as_special(&self) -> Option<&Self::SpecialSequence> {
+        if let Self::Special(seq) = &self {
+            Some(seq)
+        } else {
+            None
+        }
+    }
+
     fn kind(&self) -> PrimaryKind {
         match self {
             Self::Optional(_) => PrimaryKind::OptionalSequence,
@@ -249,6 +289,7 @@ impl IPrimary for Primary {
             Self::Grouped(_) => PrimaryKind::GroupedSequence,
             Self::MetaIdentifier(_) => PrimaryKind::MetaId,
             Self::Literal(_) => PrimaryKind::Literal,
+            Self::Special(_) => PrimaryKind::SpecialSequence,
             Self::Empty => PrimaryKind::Empty,
         }
     }
@@ -285,6 +326,7 @@ impl quote::ToTokens for Primary {
             Primary::Grouped(a) => quote! {::pb_ebnf::PrimaryRef::Grouped(#a)},
             Primary::MetaIdentifier(a) => quote! {::pb_ebnf::PrimaryRef::MetaIdentifier(#a)},
             Primary::Literal(a) => quote! {::pb_ebnf::PrimaryRef::Literal(#a)},
+            Primary::Special(a) => quote! {::pb_ebnf::PrimaryRef::Special(#a)},
             Primary::Empty => quote! {::pb_ebnf::PrimaryRef::Empty},
         })
     }
@@ -0,0 +1,213 @@
+//! Renders grammars back to canonical EBNF text.
+//!
+//! The output is accepted by [crate::parser], so that printing then parsing a
+//! syntax gives back the same tree.
+use std::fmt::Display;
+
+use crate::{
+    prelude::*, DefinitionsList, DefinitionsListRef, Factor, FactorRef, ILiteral, ISpecialSequence,
+    Primary, PrimaryRef, Rule, RuleRef, SingleDefinition, SingleDefinitionRef, Syntax, SyntaxRef,
+    Term, TermRef,
+};
+
+/// Quotes used to write terminal strings.
+///
+/// If a literal contains the preferred quote, the other one is used instead.
+/// ISO 14977 has no escape, so a literal containing both cannot round-trip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuoteStyle {
+    #[default]
+    Double,
+    Single,
+}
+
+/// How meta identifiers are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetaIdentifierStyle {
+    /// <Step Opt>
+    #[default]
+    Angle,
+    /// Step Opt
+    ///
+    /// Falls back to the angle style if the identifier cannot be read back
+    /// as a bare one (eg. it contains a -).
+    Bare,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrinterOptions {
+    /// Rules longer than this width have one alternative per line.
+    pub max_width: usize,
+    /// Aligns the | of the alternatives under the = of the rule, instead of
+    /// indenting them.
+    pub align_alternatives: bool,
+    pub quote_style: QuoteStyle,
+    pub meta_identifier_style: MetaIdentifierStyle,
+}
+
+impl Default for PrinterOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 80,
+            align_alternatives: false,
+            quote_style: QuoteStyle::default(),
+            meta_identifier_style: MetaIdentifierStyle::default(),
+        }
+    }
+}
+
+/// Prints EBNF constructs according to a set of [PrinterOptions].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Printer {
+    pub options: PrinterOptions,
+}
+
+impl Printer {
+    const INDENT: &'static str = "    ";
+
+    pub fn new(options: PrinterOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn print_syntax<S: ISyntax>(&self, syntax: &S) -> String {
+        syntax
+            .as_ref()
+            .iter()
+            .map(|rule| self.print_rule(rule))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn print_rule<R: IRule>(&self, rule: &R) -> String {
+        let lhs = self.print_meta_identifier(rule.lhs());
+        let alternatives = rule
+            .rhs()
+            .as_ref()
+            .iter()
+            .map(|def| self.print_single_definition(def))
+            .collect::<Vec<_>>();
+
+        let inline = format!("{lhs} = {};", alternatives.join(" | "));
+
+        if alternatives.len() < 2 || inline.chars().count() <= self.options.max_width {
+            return inline;
+        }
+
+        let indent = if self.options.align_alternatives {
+            " ".repeat(lhs.chars().count() + 1)
+        } else {
+            Self::INDENT.to_string()
+        };
+
+        let mut out = format!("{lhs} = {}", alternatives[0]);
+
+        for alternative in &alternatives[1..] {
+            out.push('\n');
+            out.push_str(&indent);
+            out.push_str("| ");
+            out.push_str(alternative);
+        }
+
+        out.push(';');
+        out
+    }
+
+    pub fn print_definitions_list<D: IDefinitionsList>(&self, defs: &D) -> String {
+        defs.as_ref()
+            .iter()
+            .map(|def| self.print_single_definition(def))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    pub fn print_single_definition<D: ISingleDefinition>(&self, def: &D) -> String {
+        def.as_ref()
+            .iter()
+            .map(|term| self.print_term(term))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn print_term<T: ITerm>(&self, term: &T) -> String {
+        let factor = self.print_factor(term.get_factor());
+
+        match term.get_exception() {
+            Some(exception) => format!("{factor} - {}", self.print_factor(&**exception)),
+            None => factor,
+        }
+    }
+
+    pub fn print_factor<F: IFactor>(&self, factor: &F) -> String {
+        let primary = self.print_primary(&**factor);
+
+        match factor.get_repetition() {
+            Some(rep) => format!("{rep} * {primary}"),
+            None => primary,
+        }
+    }
+
+    pub fn print_primary<P: IPrimary>(&self, primary: &P) -> String {
+        if let Some(seq) = primary.try_as_optional() {
+            format!("[{}]", self.print_definitions_list(&**seq))
+        } else if let Some(seq) = primary.try_as_repeated() {
+            format!("{{{}}}", self.print_definitions_list(seq.as_ref()))
+        } else if let Some(seq) = primary.try_as_grouped() {
+            format!("({})", self.print_definitions_list(seq.as_ref()))
+        } else if let Some(id) = primary.try_as_meta_identifier() {
+            self.print_meta_identifier(id)
+        } else if let Some(lit) = primary.try_as_literal() {
+            self.print_literal(lit)
+        } else if let Some(seq) = primary.try_as_special() {
+            self.print_special(seq)
+        } else {
+            String::default()
+        }
+    }
+
+    pub fn print_meta_identifier<M: IMetaIdentifier>(&self, id: &M) -> String {
+        let is_bare = id.split(' ').all(|fragment| {
+            fragment.starts_with(char::is_alphabetic)
+                && fragment.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
+        });
+
+        match self.options.meta_identifier_style {
+            MetaIdentifierStyle::Bare if is_bare => id.to_string(),
+            _ => format!("<{}>", &**id),
+        }
+    }
+
+    pub fn print_literal<L: ILiteral>(&self, lit: &L) -> String {
+        let quote = match self.options.quote_style {
+            QuoteStyle::Double if lit.contains('"') => '\'',
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single if lit.contains('\'') => '"',
+            QuoteStyle::Single => '\'',
+        };
+
+        format!("{quote}{}{quote}", &**lit)
+    }
+
+    pub fn print_special<S: ISpecialSequence>(&self, seq: &S) -> String {
+        format!("? {} ?", &**seq)
+    }
+}
+
+macro_rules! impl_display {
+    ($method:ident => $($ty:ty),*) => {
+        $(
+            impl Display for $ty {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str(&Printer::default().$method(self))
+                }
+            }
+        )*
+    };
+}
+
+impl_display!(print_syntax => Syntax, SyntaxRef<'_>);
+impl_display!(print_rule => Rule, RuleRef<'_>);
+impl_display!(print_definitions_list => DefinitionsList, DefinitionsListRef<'_>);
+impl_display!(print_single_definition => SingleDefinition, SingleDefinitionRef<'_>);
+impl_display!(print_term => Term, TermRef<'_>);
+impl_display!(print_factor => Factor, FactorRef<'_>);
+impl_display!(print_primary => Primary, PrimaryRef<'_>);
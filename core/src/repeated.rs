@@ -5,7 +5,9 @@ use crate::{
     single_definition::SingleDefinition,
 };
 
-pub trait IRepeatedSequence {
+pub trait IRepeatedSequence: AsRef<Self::DefinitionsList> {
+    type DefinitionsList: IDefinitionsList;
+
     fn to_owned(self) -> RepeatedSequence;
 }
 
@@ -13,6 +15,8 @@ pub trait IRepeatedSequence {
 pub struct RepeatedSequenceRef<'a>(DefinitionsListRef<'a>);
 
 impl<'a> IRepeatedSequence for RepeatedSequenceRef<'a> {
+    type DefinitionsList = DefinitionsListRef<'a>;
+
     fn to_owned(self) -> RepeatedSequence {
         RepeatedSequence(self.0.to_owned())
     }
@@ -38,6 +42,10 @@ impl<'a> AsRef<DefinitionsListRef<'a>> for RepeatedSequenceRef<'a> {
 pub struct RepeatedSequence(DefinitionsList);
 
 impl RepeatedSequence {
+    pub fn new(defs: DefinitionsList) -> Self {
+        Self(defs)
+    }
+
     pub fn is_parsable(input: &syn::parse::ParseStream) -> bool {
         input.peek(syn::token::Brace)
     }
@@ -55,7 +63,15 @@ impl Deref for RepeatedSequence {
     }
 }
 
+impl AsRef<DefinitionsList> for RepeatedSequence {
+    fn as_ref(&self) -> &DefinitionsList {
+        &self.0
+    }
+}
+
 impl IRepeatedSequence for RepeatedSequence {
+    type DefinitionsList = DefinitionsList;
+
     fn to_owned(self) -> RepeatedSequence {
         self
     }
@@ -11,6 +11,8 @@ pub trait IRule: Clone {
 
     fn lhs(&self) -> &Self::Lhs;
     fn rhs(&self) -> &Self::Rhs;
+
+    fn to_owned(self) -> Rule;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,6 +32,13 @@ impl<'a> IRule for RuleRef<'a> {
     fn rhs(&self) -> &Self::Rhs {
         &self.rhs
     }
+
+    fn to_owned(self) -> Rule {
+        Rule {
+            lhs: self.lhs.to_owned(),
+            rhs: self.rhs.to_owned(),
+        }
+    }
 }
 
 impl<'a> RuleRef<'a> {
@@ -62,6 +71,18 @@ impl IRule for Rule {
     fn rhs(&self) -> &Self::Rhs {
         &self.rhs
     }
+
+    fn to_owned(self) -> Rule {
+        self
+    }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = crate::parser::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parser::parse_rule(s)
+    }
 }
 
 impl syn::parse::Parse for Rule {
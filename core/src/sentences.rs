@@ -0,0 +1,240 @@
+//! Exhaustive enumeration of the sentences of a syntax, eg. to generate the
+//! golden inputs of a test suite.
+//!
+//! A sentence is a sequence of terminals: the text of the literals, the
+//! names of the undefined meta identifiers, and the text of the special
+//! sequences. Its length is its number of terminals.
+//!
+//! The sentences are built by length, each layer being derived from the
+//! shorter ones, so that they come in breadth-first order.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    grammar::{Grammar, Symbol},
+    prelude::*,
+    syntax::to_syntax,
+};
+
+pub type Sentence = Vec<String>;
+
+/// Enumerates the sentences derived by each rule of a syntax.
+pub struct Enumerator {
+    grammar: Grammar,
+    /// Sentences of each length, by non-terminal.
+    layers: Vec<Vec<BTreeSet<Sentence>>>,
+    max_len: usize,
+}
+
+impl Enumerator {
+    pub fn new<S: ISyntax>(syntax: &S) -> Self {
+        let grammar = Grammar::compile(to_syntax(syntax));
+        let layers = vec![Vec::default(); grammar.non_terminals.len()];
+
+        Self {
+            grammar,
+            layers,
+            max_len: 32,
+        }
+    }
+
+    /// Caps the length of the shortest sentences, 32 by default.
+    ///
+    /// The shortest sentences of a rule can be longer than its shortest
+    /// derivations, when an exception rejects them.
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = len;
+        self
+    }
+
+    /// Returns the sentences of the rule of exactly len terminals, None if
+    /// the rule is not defined.
+    pub fn sentences_of_len(&mut self, rule: &str, len: usize) -> Option<&BTreeSet<Sentence>> {
+        let id = *self.grammar.names.get(rule)?;
+        self.compute(len);
+        Some(&self.layers[id][len])
+    }
+
+    /// Iterates over the sentences of the rule up to max_len terminals, by
+    /// length then in lexicographic order, None if the rule is not defined.
+    pub fn sentences(
+        &mut self,
+        rule: &str,
+        max_len: usize,
+    ) -> Option<impl Iterator<Item = &Sentence> + '_> {
+        let id = *self.grammar.names.get(rule)?;
+        self.compute(max_len);
+        Some(self.layers[id][..=max_len].iter().flatten())
+    }
+
+    /// Returns the shortest sentences of the rule, empty if it derives none
+    /// up to the maximum length, None if the rule is not defined.
+    pub fn shortest(&mut self, rule: &str) -> Option<BTreeSet<Sentence>> {
+        let id = *self.grammar.names.get(rule)?;
+        let Some(lower) = self.lower_bounds()[id] else {
+            return Some(BTreeSet::default());
+        };
+
+        for len in lower..=self.max_len {
+            self.compute(len);
+
+            if !self.layers[id][len].is_empty() {
+                return Some(self.layers[id][len].clone());
+            }
+        }
+
+        Some(BTreeSet::default())
+    }
+
+    /// Returns the shortest sentences of every rule, by name.
+    pub fn shortest_sentences(&mut self) -> BTreeMap<String, BTreeSet<Sentence>> {
+        let names = self
+            .grammar
+            .non_terminals
+            .iter()
+            .filter_map(|nt| nt.name.clone())
+            .collect::<Vec<_>>();
+
+        names
+            .into_iter()
+            .map(|name| {
+                let sentences = self.shortest(&name).unwrap_or_default();
+                (name, sentences)
+            })
+            .collect()
+    }
+
+    /// Returns the length of the shortest derivation of each non-terminal,
+    /// regardless of the exceptions.
+    fn lower_bounds(&self) -> Vec<Option<usize>> {
+        let mut bounds = vec![None::<usize>; self.grammar.non_terminals.len()];
+
+        loop {
+            let mut changed = false;
+
+            for prod in self.grammar.productions.iter() {
+                let len = prod
+                    .rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::NonTerminal(id) => bounds[*id],
+                        Symbol::Literal(_) | Symbol::Terminal(_) => Some(1),
+                    })
+                    .try_fold(0, |sum, len| Some(sum + len?));
+
+                if len.is_some() && len < bounds[prod.lhs].or(Some(usize::MAX)) {
+                    bounds[prod.lhs] = len;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                return bounds;
+            }
+        }
+    }
+
+    /// Computes the layers up to len terminals.
+    fn compute(&mut self, len: usize) {
+        while self.layers[0].len() <= len {
+            self.compute_layer();
+        }
+    }
+
+    /// Computes the next layer.
+    ///
+    /// A derivation can go through a non-terminal of the same length, so the
+    /// layer is computed to a fixpoint. The exceptions are checked against
+    /// the previous computation of the layer, until it is stable; as excepted
+    /// factors are not recursive, their sentences are known after a few
+    /// rounds.
+    fn compute_layer(&mut self) {
+        let n = self.grammar.non_terminals.len();
+        let mut excepted = vec![BTreeSet::<Sentence>::default(); n];
+
+        for _ in 0..=n {
+            let layer = self.fixpoint(&excepted);
+
+            if layer == excepted {
+                break;
+            }
+
+            excepted = layer;
+        }
+
+        for (layers, sentences) in self.layers.iter_mut().zip(excepted) {
+            layers.push(sentences);
+        }
+    }
+
+    fn fixpoint(&self, excepted: &[BTreeSet<Sentence>]) -> Vec<BTreeSet<Sentence>> {
+        let len = self.layers[0].len();
+        let mut layer = vec![BTreeSet::<Sentence>::default(); self.grammar.non_terminals.len()];
+
+        loop {
+            let mut changed = false;
+
+            for prod in self.grammar.productions.iter() {
+                for sentence in self.sequences(&layer, &prod.rhs, len) {
+                    if prod
+                        .exception
+                        .is_some_and(|id| excepted[id].contains(&sentence))
+                    {
+                        continue;
+                    }
+
+                    changed |= layer[prod.lhs].insert(sentence);
+                }
+            }
+
+            if !changed {
+                return layer;
+            }
+        }
+    }
+
+    /// Returns the sentences of len terminals derived by the symbols, the
+    /// layer holding the ones of the length being computed found so far.
+    fn sequences(
+        &self,
+        layer: &[BTreeSet<Sentence>],
+        symbols: &[Symbol],
+        len: usize,
+    ) -> BTreeSet<Sentence> {
+        let Some((first, rest)) = symbols.split_first() else {
+            return match len {
+                0 => BTreeSet::from([Sentence::default()]),
+                _ => BTreeSet::default(),
+            };
+        };
+
+        let mut sentences = BTreeSet::<Sentence>::default();
+        let current = self.layers[0].len();
+
+        let heads = |k: usize| -> Vec<Sentence> {
+            match first {
+                Symbol::NonTerminal(id) if k == current => layer[*id].iter().cloned().collect(),
+                Symbol::NonTerminal(id) => self.layers[*id][k].iter().cloned().collect(),
+                symbol if k == 1 => vec![vec![symbol.as_terminal().unwrap().to_string()]],
+                _ => Vec::default(),
+            }
+        };
+
+        for k in 0..=len {
+            let heads = heads(k);
+
+            if heads.is_empty() {
+                continue;
+            }
+
+            let tails = self.sequences(layer, rest, len - k);
+
+            for head in heads.iter() {
+                for tail in tails.iter() {
+                    sentences.insert(head.iter().chain(tail.iter()).cloned().collect());
+                }
+            }
+        }
+
+        sentences
+    }
+}
@@ -48,6 +48,30 @@ impl SingleDefinition {
     }
 }
 
+/// Builds a single definition, without the empty terms unless it is empty.
+pub(crate) fn sequence(terms: Vec<Term>) -> SingleDefinition {
+    let def = terms
+        .into_iter()
+        .filter(|term| !is_empty_term(term))
+        .collect::<SingleDefinition>();
+
+    if def.is_empty() {
+        SingleDefinition::empty()
+    } else {
+        def
+    }
+}
+
+/// Returns true if the definition derives the empty sequence only, like
+/// [SingleDefinition::empty].
+pub(crate) fn is_empty(def: &SingleDefinition) -> bool {
+    def.iter().all(is_empty_term)
+}
+
+pub(crate) fn is_empty_term(term: &Term) -> bool {
+    term.exception.is_none() && term.primary == Primary::Empty
+}
+
 impl ISingleDefinition for SingleDefinition {
     type Term = Term;
 
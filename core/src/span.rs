@@ -0,0 +1,56 @@
+//! Location of the meta identifiers of a syntax.
+//!
+//! The nodes of a syntax do not keep the tokens they were parsed from, the
+//! `ebnf!` macro maps the positions to the spans of its input instead.
+use crate::{visit::walk_single_definition, MetaIdentifier, Primary, Syntax};
+
+/// Position of a meta identifier in a syntax.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NodePosition {
+    /// The lhs of the rule.
+    Lhs { rule: usize },
+    /// The index-th meta identifier of the alternative of the rule, in
+    /// source order, counting the ones nested in sequences and exceptions.
+    Reference {
+        rule: usize,
+        alternative: usize,
+        index: usize,
+    },
+}
+
+impl NodePosition {
+    /// Returns the position of a meta identifier borrowed from the syntax,
+    /// None if it is not one of its nodes.
+    pub fn of(syntax: &Syntax, id: &MetaIdentifier) -> Option<Self> {
+        for (rule, r) in syntax.iter().enumerate() {
+            if std::ptr::eq(&r.lhs, id) {
+                return Some(NodePosition::Lhs { rule });
+            }
+
+            for (alternative, def) in r.rhs.iter().enumerate() {
+                let mut index = 0;
+                let mut found = None;
+
+                walk_single_definition(def, &mut |primary| {
+                    if let Primary::MetaIdentifier(other) = primary {
+                        if found.is_none() && std::ptr::eq(other, id) {
+                            found = Some(index);
+                        }
+
+                        index += 1;
+                    }
+                });
+
+                if let Some(index) = found {
+                    return Some(NodePosition::Reference {
+                        rule,
+                        alternative,
+                        index,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
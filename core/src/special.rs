@@ -0,0 +1,74 @@
+use pb_bnf::{
+    prelude::*,
+    symbol::{Symbol, SymbolRef},
+};
+use std::ops::Deref;
+
+/// A special sequence (? ... ?), whose meaning is left to the implementation.
+pub trait ISpecialSequence: Deref<Target = str> {
+    fn to_owned(self) -> SpecialSequence;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpecialSequenceRef<'a>(SymbolRef<'a>);
+
+impl<'a> SpecialSequenceRef<'a> {
+    pub const fn new(value: &'a str) -> Self {
+        Self(SymbolRef::new(value))
+    }
+}
+
+impl ISpecialSequence for SpecialSequenceRef<'_> {
+    fn to_owned(self) -> SpecialSequence {
+        SpecialSequence(ISymbol::to_owned(&self.0))
+    }
+}
+
+impl Deref for SpecialSequenceRef<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecialSequence(Symbol);
+
+impl SpecialSequence {
+    pub fn new<S: ToString>(value: S) -> Self {
+        Self(Symbol::from(value.to_string()))
+    }
+
+    pub fn borrow(&self) -> SpecialSequenceRef<'_> {
+        SpecialSequenceRef(ISymbol::borrow(&self.0))
+    }
+
+    pub fn as_symbol(&self) -> &Symbol {
+        &self.0
+    }
+}
+
+impl ISpecialSequence for SpecialSequence {
+    fn to_owned(self) -> SpecialSequence {
+        self
+    }
+}
+
+impl Deref for SpecialSequence {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl quote::ToTokens for SpecialSequence {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        use quote::quote;
+        let value = &self.0.to_string();
+        tokens.extend(quote! {
+            ::pb_ebnf::SpecialSequenceRef::new(#value)
+        })
+    }
+}
@@ -1,24 +1,115 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+};
 
 use crate::{
+    classify::Classification,
     into_slice,
     rule::{IRule, Rule, RuleRef},
+    symbol::SymbolRef,
+    validation::{self, Diagnostic},
 };
 
 pub trait ISyntax: AsRef<[Self::Rule]> {
     type Rule: IRule;
+
+    fn to_owned(self) -> Syntax;
+
+    /// Validates the syntax, starting from the lhs of the first rule.
+    fn validate(&self) -> Vec<Diagnostic> {
+        validation::validate(&to_syntax(self), None)
+    }
+
+    /// Validates the syntax, starting from the given symbol.
+    fn validate_from(&self, start: &str) -> Vec<Diagnostic> {
+        validation::validate(&to_syntax(self), Some(start))
+    }
+}
+
+/// Returns an owned copy of the rules of the syntax.
+pub(crate) fn to_syntax<S: ISyntax + ?Sized>(syntax: &S) -> Syntax {
+    syntax
+        .as_ref()
+        .iter()
+        .map(|rule| IRule::to_owned(rule.clone()))
+        .collect()
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct SyntaxRef<'a>(&'a [RuleRef<'a>]);
+/// The index of the rules of a syntax by name, built at compile time by
+/// `ebnf!`: the lhs of the rules paired with the first rule defining them,
+/// sorted by lhs.
+pub type RuleIndex<'a> = [(&'a str, usize)];
+
+#[derive(Debug, Clone, Copy)]
+pub struct SyntaxRef<'a>(&'a [RuleRef<'a>], Option<&'a RuleIndex<'a>>);
 
 impl<'a> ISyntax for SyntaxRef<'a> {
     type Rule = RuleRef<'a>;
+
+    fn to_owned(self) -> Syntax {
+        Syntax(self.0.iter().copied().map(IRule::to_owned).collect())
+    }
+}
+
+impl PartialEq for SyntaxRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
 }
 
+impl Eq for SyntaxRef<'_> {}
+
 impl<'a> SyntaxRef<'a> {
     pub const fn new(rules: &'a [RuleRef<'a>]) -> Self {
-        Self(rules)
+        Self(rules, None)
+    }
+
+    /// The index maps the lhs of each rule to the first rule defining it, and
+    /// must be sorted by lhs.
+    pub const fn with_index(rules: &'a [RuleRef<'a>], index: &'a RuleIndex<'a>) -> Self {
+        Self(rules, Some(index))
+    }
+
+    /// Returns the first rule defining the meta identifier, looked up in the
+    /// index if there is one.
+    pub fn get(&self, name: &str) -> Option<RuleRef<'a>> {
+        match self.1 {
+            Some(index) => index
+                .binary_search_by(|(lhs, _)| (*lhs).cmp(name))
+                .ok()
+                .map(|i| self.0[index[i].1]),
+            None => self.0.iter().find(|rule| &*rule.lhs == name).copied(),
+        }
+    }
+
+    /// Iterates over the terminals of the syntax.
+    ///
+    /// Terminals are the literals, and the meta identifiers which are
+    /// referenced but never defined by a rule (eg. <Integer>).
+    pub fn iter_terminals(&self) -> impl Iterator<Item = SymbolRef<'a>> {
+        Classification::new(self.0)
+            .terminals()
+            .map(SymbolRef::new)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Iterates over the non-terminals of the syntax, that is the meta
+    /// identifiers defined by a rule.
+    pub fn iter_non_terminals(&self) -> impl Iterator<Item = SymbolRef<'a>> {
+        Classification::new(self.0)
+            .non_terminals
+            .into_iter()
+            .map(SymbolRef::new)
+    }
+
+    /// Iterates over the meta identifiers referenced but never defined.
+    pub fn iter_undefined_symbols(&self) -> impl Iterator<Item = SymbolRef<'a>> {
+        Classification::new(self.0)
+            .undefined
+            .into_iter()
+            .map(SymbolRef::new)
     }
 }
 
@@ -37,7 +128,7 @@ impl IntoIterator for Syntax {
     type IntoIter = <Vec<Rule> as IntoIterator>::IntoIter;
 
     fn into_iter(self) -> Self::IntoIter {
-        todo!()
+        self.0.into_iter()
     }
 }
 
@@ -49,12 +140,51 @@ impl FromIterator<Rule> for Syntax {
 
 impl ISyntax for Syntax {
     type Rule = Rule;
+
+    fn to_owned(self) -> Syntax {
+        self
+    }
+
+    fn validate(&self) -> Vec<Diagnostic> {
+        validation::validate(self, None)
+    }
+
+    fn validate_from(&self, start: &str) -> Vec<Diagnostic> {
+        validation::validate(self, Some(start))
+    }
 }
 
 impl Syntax {
     pub fn push(&mut self, rule: Rule) {
         self.0.push(rule)
     }
+
+    /// Iterates over the terminals of the syntax.
+    ///
+    /// See [SyntaxRef::iter_terminals].
+    pub fn iter_terminals(&self) -> impl Iterator<Item = SymbolRef<'_>> {
+        Classification::new(&self.0)
+            .terminals()
+            .map(SymbolRef::new)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// Iterates over the non-terminals of the syntax.
+    pub fn iter_non_terminals(&self) -> impl Iterator<Item = SymbolRef<'_>> {
+        Classification::new(&self.0)
+            .non_terminals
+            .into_iter()
+            .map(SymbolRef::new)
+    }
+
+    /// Iterates over the meta identifiers referenced but never defined.
+    pub fn iter_undefined_symbols(&self) -> impl Iterator<Item = SymbolRef<'_>> {
+        Classification::new(&self.0)
+            .undefined
+            .into_iter()
+            .map(SymbolRef::new)
+    }
 }
 
 impl Deref for Syntax {
@@ -77,6 +207,14 @@ impl AsRef<[Rule]> for Syntax {
     }
 }
 
+impl std::str::FromStr for Syntax {
+    type Err = crate::parser::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::parser::parse_syntax(s)
+    }
+}
+
 impl syn::parse::Parse for Syntax {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let mut ls = Vec::<Rule>::default();
@@ -96,3 +234,47 @@ impl quote::ToTokens for Syntax {
         })
     }
 }
+
+/// A syntax along with the index of its rules by name, the owned counterpart
+/// of [SyntaxRef::with_index], hashed when the syntax is converted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexedSyntax {
+    syntax: Syntax,
+    index: HashMap<String, usize>,
+}
+
+impl IndexedSyntax {
+    /// Returns the first rule defining the meta identifier.
+    pub fn get(&self, name: &str) -> Option<&Rule> {
+        self.index.get(name).map(|&i| &self.syntax[i])
+    }
+
+    /// Returns the index of the first rule defining the meta identifier.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    pub fn into_syntax(self) -> Syntax {
+        self.syntax
+    }
+}
+
+impl From<Syntax> for IndexedSyntax {
+    fn from(syntax: Syntax) -> Self {
+        let mut index = HashMap::<String, usize>::default();
+
+        for (i, rule) in syntax.iter().enumerate() {
+            index.entry(rule.lhs.to_string()).or_insert(i);
+        }
+
+        Self { syntax, index }
+    }
+}
+
+impl Deref for IndexedSyntax {
+    type Target = Syntax;
+
+    fn deref(&self) -> &Self::Target {
+        &self.syntax
+    }
+}
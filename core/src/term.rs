@@ -121,6 +121,7 @@ impl syn::parse::Parse for Term {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let factor = input.parse::<Factor>()?;
         let exception = if input.peek(syn::Token![-]) {
+            input.parse::<syn::Token![-]>()?;
             input.parse::<Exception>().map(Some)?
         } else {
             None
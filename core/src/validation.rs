@@ -0,0 +1,381 @@
+//! Semantic checks over a syntax.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Display,
+};
+
+use crate::{
+    single_definition::is_empty,
+    span::NodePosition,
+    visit::{walk_primaries, walk_primary, walk_single_definition},
+    DefinitionsList, Factor, MetaIdentifier, Primary, SingleDefinition, Syntax,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A meta identifier is referenced but no rule defines it.
+    UndefinedMetaIdentifier(String),
+    /// The rule's lhs is already defined by the rule at index `first`.
+    DuplicateRule { name: String, first: usize },
+    /// The rule cannot be reached from the start symbol.
+    UnreachableRule(String),
+    /// The rule can never derive a string made of terminals only.
+    NonProductiveRule(String),
+    /// An empty alternative next to non-empty ones, like a trailing `| ;`.
+    EmptyAlternative,
+    /// An excepted factor references the recursive rule, whereas the
+    /// standard requires it to be replaceable by a factor without meta
+    /// identifiers.
+    RecursiveException(String),
+}
+
+/// A problem found in a syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    /// Index of the rule in the syntax.
+    pub rule: usize,
+    /// Index of the alternative in the rule's definitions list, if the
+    /// diagnostic is about a specific one.
+    pub alternative: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn severity(&self) -> Severity {
+        match self.kind {
+            DiagnosticKind::UndefinedMetaIdentifier(_)
+            | DiagnosticKind::DuplicateRule { .. }
+            | DiagnosticKind::RecursiveException(_) => Severity::Error,
+            DiagnosticKind::UnreachableRule(_)
+            | DiagnosticKind::NonProductiveRule(_)
+            | DiagnosticKind::EmptyAlternative => Severity::Warning,
+        }
+    }
+
+    /// Returns the meta identifier the diagnostic points at: the first
+    /// reference to the symbol in the alternative, or the lhs of the rule.
+    pub fn meta_identifier<'s>(&self, syntax: &'s Syntax) -> Option<&'s MetaIdentifier> {
+        let rule = syntax.get(self.rule)?;
+
+        let name = match &self.kind {
+            DiagnosticKind::UndefinedMetaIdentifier(name)
+            | DiagnosticKind::RecursiveException(name) => name,
+            _ => return Some(&rule.lhs),
+        };
+
+        let mut found = None;
+
+        if let Some(def) = self.alternative.and_then(|j| rule.rhs.get(j)) {
+            walk_single_definition(def, &mut |primary| match primary {
+                Primary::MetaIdentifier(id) if found.is_none() && &**id == name => found = Some(id),
+                _ => {}
+            });
+        }
+
+        found.or(Some(&rule.lhs))
+    }
+
+    /// Returns the position of the meta identifier the diagnostic points at,
+    /// see [Diagnostic::meta_identifier].
+    pub fn position(&self, syntax: &Syntax) -> Option<NodePosition> {
+        NodePosition::of(syntax, self.meta_identifier(syntax)?)
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rule #{}", self.rule)?;
+
+        if let Some(alternative) = self.alternative {
+            write!(f, ", alternative #{alternative}")?;
+        }
+
+        match &self.kind {
+            DiagnosticKind::UndefinedMetaIdentifier(name) => {
+                write!(f, ": <{name}> is not defined")
+            }
+            DiagnosticKind::DuplicateRule { name, first } => {
+                write!(f, ": <{name}> is already defined by rule #{first}")
+            }
+            DiagnosticKind::UnreachableRule(name) => {
+                write!(f, ": <{name}> is unreachable from the start symbol")
+            }
+            DiagnosticKind::NonProductiveRule(name) => {
+                write!(f, ": <{name}> can never derive a terminal string")
+            }
+            DiagnosticKind::EmptyAlternative => write!(f, ": empty alternative"),
+            DiagnosticKind::RecursiveException(name) => {
+                write!(f, ": the exception references the recursive rule <{name}>")
+            }
+        }
+    }
+}
+
+/// Validates the syntax, the start symbol being the lhs of the first rule
+/// unless another one is given.
+pub fn validate(syntax: &Syntax, start: Option<&str>) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::<Diagnostic>::default();
+
+    check_duplicates(syntax, &mut diagnostics);
+    check_undefined(syntax, &mut diagnostics);
+    check_empty_alternatives(syntax, &mut diagnostics);
+    check_exceptions(syntax, &mut diagnostics);
+
+    if let Some(start) = start.or_else(|| syntax.first().map(|rule| &*rule.lhs)) {
+        check_reachability(syntax, start, &mut diagnostics);
+    }
+
+    check_productivity(syntax, &mut diagnostics);
+
+    diagnostics
+}
+
+fn check_duplicates(syntax: &Syntax, diagnostics: &mut Vec<Diagnostic>) {
+    let mut defined = HashMap::<&str, usize>::default();
+
+    for (i, rule) in syntax.iter().enumerate() {
+        if let Some(&first) = defined.get(&*rule.lhs) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::DuplicateRule {
+                    name: rule.lhs.to_string(),
+                    first,
+                },
+                rule: i,
+                alternative: None,
+            });
+        } else {
+            defined.insert(&rule.lhs, i);
+        }
+    }
+}
+
+fn check_undefined(syntax: &Syntax, diagnostics: &mut Vec<Diagnostic>) {
+    let defined = syntax.iter().map(|rule| &*rule.lhs).collect::<HashSet<_>>();
+
+    for (i, rule) in syntax.iter().enumerate() {
+        for (j, def) in rule.rhs.iter().enumerate() {
+            let mut reported = HashSet::<&str>::default();
+
+            walk_single_definition(def, &mut |primary| {
+                if let Primary::MetaIdentifier(id) = primary {
+                    if !defined.contains(&**id) && reported.insert(id) {
+                        diagnostics.push(Diagnostic {
+                            kind: DiagnosticKind::UndefinedMetaIdentifier(id.to_string()),
+                            rule: i,
+                            alternative: Some(j),
+                        });
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn check_empty_alternatives(syntax: &Syntax, diagnostics: &mut Vec<Diagnostic>) {
+    for (i, rule) in syntax.iter().enumerate() {
+        for (j, def) in rule.rhs.iter().enumerate() {
+            let mut found = rule.rhs.len() > 1 && is_empty(def);
+
+            // Nested sequences, such as [ "a" | ].
+            walk_single_definition(def, &mut |primary| {
+                found |= primary
+                    .try_as_definitions_list()
+                    .is_some_and(|defs| defs.len() > 1 && defs.iter().any(is_empty));
+            });
+
+            if found {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::EmptyAlternative,
+                    rule: i,
+                    alternative: Some(j),
+                });
+            }
+        }
+    }
+}
+
+fn check_exceptions(syntax: &Syntax, diagnostics: &mut Vec<Diagnostic>) {
+    let rules = rules_by_name(syntax);
+
+    // The rules reachable from the rhs of each rule.
+    let reachable = rules
+        .iter()
+        .map(|(&lhs, rhs)| {
+            let mut reached = HashSet::<&str>::default();
+            let mut queue = VecDeque::from_iter(rhs.iter().copied());
+
+            while let Some(defs) = queue.pop_front() {
+                walk_primaries(defs, &mut |primary| {
+                    if let Primary::MetaIdentifier(id) = primary {
+                        if reached.insert(id) {
+                            queue.extend(rules.get(&**id).into_iter().flatten().copied());
+                        }
+                    }
+                });
+            }
+
+            (lhs, reached)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let is_recursive = |rule: &str| reachable.get(rule).is_some_and(|set| set.contains(rule));
+
+    for (i, rule) in syntax.iter().enumerate() {
+        for (j, def) in rule.rhs.iter().enumerate() {
+            let mut recursive = Vec::<&str>::default();
+
+            walk_exceptions(def, &mut |factor| {
+                walk_primary(&factor.primary, &mut |primary| {
+                    let Primary::MetaIdentifier(id) = primary else {
+                        return;
+                    };
+
+                    let found = std::iter::once(&**id)
+                        .chain(reachable.get(&**id).into_iter().flatten().copied())
+                        .filter(|rule| is_recursive(rule));
+
+                    for name in found {
+                        if !recursive.contains(&name) {
+                            recursive.push(name);
+                        }
+                    }
+                });
+            });
+
+            for name in recursive {
+                diagnostics.push(Diagnostic {
+                    kind: DiagnosticKind::RecursiveException(name.to_string()),
+                    rule: i,
+                    alternative: Some(j),
+                });
+            }
+        }
+    }
+}
+
+/// Visits the excepted factors of a single definition, including the ones
+/// nested in sequences.
+fn walk_exceptions<'a>(def: &'a SingleDefinition, f: &mut impl FnMut(&'a Factor)) {
+    for term in def.iter() {
+        if let Some(exception) = &term.exception {
+            f(exception);
+        }
+
+        for def in term
+            .primary
+            .try_as_definitions_list()
+            .into_iter()
+            .flat_map(|defs| defs.iter())
+        {
+            walk_exceptions(def, f);
+        }
+    }
+}
+
+fn rules_by_name(syntax: &Syntax) -> HashMap<&str, Vec<&DefinitionsList>> {
+    syntax.iter().map(|rule| (&*rule.lhs, &rule.rhs)).fold(
+        HashMap::<&str, Vec<&DefinitionsList>>::default(),
+        |mut acc, (lhs, rhs)| {
+            acc.entry(lhs).or_default().push(rhs);
+            acc
+        },
+    )
+}
+
+fn check_reachability(syntax: &Syntax, start: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let rules = rules_by_name(syntax);
+
+    let mut reached = HashSet::<&str>::from([start]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(lhs) = queue.pop_front() {
+        for rhs in rules.get(lhs).into_iter().flatten() {
+            walk_primaries(rhs, &mut |primary| {
+                if let Primary::MetaIdentifier(id) = primary {
+                    if reached.insert(id) {
+                        queue.push_back(id);
+                    }
+                }
+            });
+        }
+    }
+
+    for (i, rule) in syntax.iter().enumerate() {
+        if !reached.contains(&*rule.lhs) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::UnreachableRule(rule.lhs.to_string()),
+                rule: i,
+                alternative: None,
+            });
+        }
+    }
+}
+
+fn check_productivity(syntax: &Syntax, diagnostics: &mut Vec<Diagnostic>) {
+    let defined = syntax.iter().map(|rule| &*rule.lhs).collect::<HashSet<_>>();
+
+    let mut productive = HashSet::<&str>::default();
+
+    // Iterates until a fixpoint is reached.
+    loop {
+        let before = productive.len();
+
+        for rule in syntax.iter() {
+            let is_productive = Productivity {
+                defined: &defined,
+                productive: &productive,
+            }
+            .definitions_list(&rule.rhs);
+
+            if is_productive {
+                productive.insert(&rule.lhs);
+            }
+        }
+
+        if productive.len() == before {
+            break;
+        }
+    }
+
+    for (i, rule) in syntax.iter().enumerate() {
+        if !productive.contains(&*rule.lhs) {
+            diagnostics.push(Diagnostic {
+                kind: DiagnosticKind::NonProductiveRule(rule.lhs.to_string()),
+                rule: i,
+                alternative: None,
+            });
+        }
+    }
+}
+
+struct Productivity<'a, 'b> {
+    defined: &'b HashSet<&'a str>,
+    productive: &'b HashSet<&'a str>,
+}
+
+impl Productivity<'_, '_> {
+    fn definitions_list(&self, defs: &DefinitionsList) -> bool {
+        defs.iter().any(|def| {
+            def.iter()
+                .all(|term| term.repetition == Some(0) || self.primary(&term.factor.primary))
+        })
+    }
+
+    fn primary(&self, primary: &Primary) -> bool {
+        match primary {
+            Primary::Optional(_) | Primary::Repeated(_) => true,
+            Primary::Grouped(seq) => self.definitions_list(seq),
+            // Undefined meta identifiers are provided from outside (eg. a lexer).
+            Primary::MetaIdentifier(id) => {
+                !self.defined.contains(&**id) || self.productive.contains(&**id)
+            }
+            Primary::Literal(_) | Primary::Special(_) | Primary::Empty => true,
+        }
+    }
+}
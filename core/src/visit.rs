@@ -0,0 +1,80 @@
+use std::ops::Deref;
+
+use crate::{prelude::*, primary::PrimaryKind, DefinitionsList, Primary, SingleDefinition};
+
+/// Visits every primary of a definitions list, including the ones nested in
+/// sequences and in exceptions.
+pub(crate) fn walk_primaries<'a>(defs: &'a DefinitionsList, f: &mut impl FnMut(&'a Primary)) {
+    for def in defs.iter() {
+        walk_single_definition(def, f);
+    }
+}
+
+/// Visits every primary of a single definition, see [walk_primaries].
+pub(crate) fn walk_single_definition<'a>(
+    def: &'a SingleDefinition,
+    f: &mut impl FnMut(&'a Primary),
+) {
+    for term in def.iter() {
+        walk_primary(&term.factor.primary, f);
+
+        if let Some(exception) = &term.exception {
+            walk_primary(&exception.primary, f);
+        }
+    }
+}
+
+/// Returns true if a term of the definitions list, including in sequences,
+/// has an exception.
+pub(crate) fn has_exception(defs: &DefinitionsList) -> bool {
+    defs.iter().flat_map(|def| def.iter()).any(|term| {
+        term.exception.is_some()
+            || term
+                .primary
+                .try_as_definitions_list()
+                .is_some_and(has_exception)
+    })
+}
+
+pub(crate) fn walk_primary<'a>(primary: &'a Primary, f: &mut impl FnMut(&'a Primary)) {
+    f(primary);
+
+    if let Some(defs) = primary.try_as_definitions_list() {
+        walk_primaries(defs, f);
+    }
+}
+
+/// Visits the meta identifiers, literals and special sequences of a
+/// definitions list of either representation, see [walk_primaries].
+pub(crate) fn walk_symbols<'a, D: IDefinitionsList>(
+    defs: &'a D,
+    f: &mut impl FnMut(PrimaryKind, &'a str),
+) {
+    for def in defs.as_ref() {
+        for term in def.as_ref() {
+            walk_factor_symbols(term.get_factor(), f);
+
+            if let Some(exception) = term.get_exception() {
+                walk_factor_symbols(exception.deref(), f);
+            }
+        }
+    }
+}
+
+fn walk_factor_symbols<'a, F: IFactor>(factor: &'a F, f: &mut impl FnMut(PrimaryKind, &'a str)) {
+    let primary: &'a F::Primary = factor;
+
+    if let Some(seq) = primary.try_as_optional() {
+        walk_symbols(seq.deref(), f);
+    } else if let Some(seq) = primary.try_as_repeated() {
+        walk_symbols(seq.as_ref(), f);
+    } else if let Some(seq) = primary.try_as_grouped() {
+        walk_symbols(seq.as_ref(), f);
+    } else if let Some(id) = primary.try_as_meta_identifier() {
+        f(PrimaryKind::MetaId, id);
+    } else if let Some(lit) = primary.try_as_literal() {
+        f(PrimaryKind::Literal, lit);
+    } else if let Some(seq) = primary.try_as_special() {
+        f(PrimaryKind::SpecialSequence, seq);
+    }
+}
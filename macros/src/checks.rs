@@ -0,0 +1,105 @@
+use pb_ebnf_core::{
+    analysis::Analysis,
+    left_recursion,
+    span::NodePosition,
+    validation::{validate, DiagnosticKind},
+    Syntax,
+};
+
+use crate::spans::Spans;
+
+/// Optional checks, enabled with `#![deny(...)]` at the top of the macro input.
+#[derive(Default)]
+pub struct Checks {
+    /// Every referenced meta identifier must be defined by a rule.
+    pub undefined_symbols: bool,
+    /// No rule may be left-recursive.
+    pub left_recursion: bool,
+}
+
+impl Checks {
+    pub fn from_attributes(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut checks = Self::default();
+
+        for attr in attrs {
+            if attr.path().is_ident("emit") {
+                continue;
+            }
+
+            if !attr.path().is_ident("deny") {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "expecting #![deny(...)] or #![emit(...)]",
+                ));
+            }
+
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("undefined_symbols") {
+                    checks.undefined_symbols = true;
+                } else if meta.path.is_ident("left_recursion") {
+                    checks.left_recursion = true;
+                } else {
+                    return Err(meta.error("expecting undefined_symbols or left_recursion"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(checks)
+    }
+
+    /// Runs the checks, duplicate rules and exceptions referencing a
+    /// recursive rule are always reported.
+    pub fn run(&self, syntax: &Syntax, spans: &Spans) -> Vec<syn::Error> {
+        let mut errors = validate(syntax, None)
+            .into_iter()
+            .filter_map(|diagnostic| {
+                let message = match &diagnostic.kind {
+                    DiagnosticKind::DuplicateRule { name, .. } => {
+                        format!("<{name}> is already defined")
+                    }
+                    DiagnosticKind::UndefinedMetaIdentifier(name) if self.undefined_symbols => {
+                        format!("<{name}> is not defined")
+                    }
+                    DiagnosticKind::RecursiveException(name) => {
+                        format!("the exception references the recursive rule <{name}>")
+                    }
+                    _ => return None,
+                };
+
+                Some(spans.error(diagnostic.position(syntax), message))
+            })
+            .collect::<Vec<_>>();
+
+        if self.left_recursion {
+            errors.extend(check_left_recursion(syntax, spans));
+        }
+
+        errors
+    }
+}
+
+fn check_left_recursion(syntax: &Syntax, spans: &Spans) -> Vec<syn::Error> {
+    let analysis = Analysis::new(syntax);
+
+    left_recursion::find_cycles_with(syntax, &analysis)
+        .into_iter()
+        .map(|recursion| {
+            // Points at the symbol through which the first rule recurses.
+            let next = &recursion.cycle[1 % recursion.cycle.len()];
+            let position = syntax
+                .iter()
+                .find(|rule| *rule.lhs == *recursion.cycle[0])
+                .and_then(|rule| {
+                    analysis
+                        .transitive_of_definitions_list(&rule.rhs)
+                        .into_iter()
+                        .find(|id| ***id == **next)
+                })
+                .and_then(|id| NodePosition::of(syntax, id));
+
+            spans.error(position, format!("left recursion: {recursion}"))
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+use pb_ebnf_core::{ll1_table::ParseTable, Syntax};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+
+/// Extra data emitted next to the syntax, enabled with `#![emit(...)]` at the
+/// top of the macro input.
+#[derive(Default)]
+pub struct Emit {
+    /// Emits an `Ll1Grammar`, the syntax and its LL(1) parse table.
+    pub ll1_table: bool,
+}
+
+impl Emit {
+    pub fn from_attributes(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut emit = Self::default();
+
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("emit")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("ll1_table") {
+                    emit.ll1_table = true;
+                } else {
+                    return Err(meta.error("expecting ll1_table"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        Ok(emit)
+    }
+
+    /// Expands to the syntax, along with the requested data.
+    pub fn expand(&self, syntax: &Syntax) -> Result<TokenStream, syn::Error> {
+        let indexed = indexed(syntax);
+
+        if !self.ll1_table {
+            return Ok(indexed);
+        }
+
+        let table =
+            ParseTable::build(syntax).map_err(|error| syn::Error::new(Span::call_site(), error))?;
+
+        Ok(quote! { ::pb_ebnf::ll1_table::Ll1Grammar::new(#indexed, #table) })
+    }
+
+    /// Expands to an empty value of the expected type, once errors have been
+    /// reported.
+    pub fn placeholder(&self) -> TokenStream {
+        if self.ll1_table {
+            quote! {
+                ::pb_ebnf::ll1_table::Ll1Grammar::new(
+                    ::pb_ebnf::SyntaxRef::new(&[]),
+                    ::pb_ebnf::ll1_table::ParseTableRef::new(&[], &[], &[]),
+                )
+            }
+        } else {
+            quote! { ::pb_ebnf::SyntaxRef::new(&[]) }
+        }
+    }
+}
+
+/// Expands to the syntax along with the index of its rules, see
+/// `SyntaxRef::with_index`.
+fn indexed(syntax: &Syntax) -> TokenStream {
+    // The first rule defining each meta identifier, sorted by name.
+    let mut index = Vec::<(&str, usize)>::default();
+
+    for (i, rule) in syntax.iter().enumerate() {
+        if let Err(at) = index.binary_search_by(|(lhs, _)| (*lhs).cmp(&rule.lhs)) {
+            index.insert(at, (&rule.lhs, i));
+        }
+    }
+
+    let rules = syntax.iter();
+    let index = index.iter().map(|(lhs, i)| quote!((#lhs, #i)));
+
+    quote! {
+        ::pb_ebnf::SyntaxRef::with_index(&[#(#rules),*], &[#(#index),*])
+    }
+}
@@ -1,10 +1,86 @@
-use pb_ebnf_core::syntax::OwnedSyntax;
+mod checks;
+mod emit;
+mod spans;
+
+use checks::Checks;
+use emit::Emit;
+use pb_ebnf_core::syntax::Syntax;
 use proc_macro::TokenStream;
 use quote::quote;
+use spans::Spans;
 use syn::parse_macro_input;
 
+struct EbnfInput {
+    checks: Checks,
+    emit: Emit,
+    spans: Spans,
+    syntax: Syntax,
+}
+
+impl syn::parse::Parse for EbnfInput {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(syn::Attribute::parse_inner)?;
+
+        Ok(Self {
+            checks: Checks::from_attributes(&attrs)?,
+            emit: Emit::from_attributes(&attrs)?,
+            spans: Spans::new(input.fork().parse()?),
+            syntax: input.parse()?,
+        })
+    }
+}
+
+/// Declares a syntax in EBNF.
+///
+/// The syntax comes with an index of its rules sorted by name, built at
+/// compile time, so that `SyntaxRef::get` finds a rule by name with a binary
+/// search. The index is lost by `to_owned`, see `IndexedSyntax` for an owned
+/// syntax.
+///
+/// Duplicate rules are rejected. More checks can be enabled with an inner
+/// attribute at the top of the syntax:
+///
+/// ```ignore
+/// const SYNTAX: StaticSyntax = ebnf! {
+///     #![deny(undefined_symbols, left_recursion)]
+///     <digit> = '0' | '1';
+/// };
+/// ```
+///
+/// `#![emit(ll1_table)]` also builds the LL(1) parse table of the syntax at
+/// compile time, the macro then expanding to an `Ll1Grammar`:
+///
+/// ```ignore
+/// const GRAMMAR: Ll1Grammar<'static> = ebnf! {
+///     #![emit(ll1_table)]
+///     <list> = '[', { <digit> }, ']';
+///     <digit> = '0' | '1';
+/// };
+/// ```
 #[proc_macro]
 pub fn ebnf(input: TokenStream) -> TokenStream {
-    let syntax: OwnedSyntax = parse_macro_input!(input);
-    quote! {#syntax}.into()
+    let EbnfInput {
+        checks,
+        emit,
+        spans,
+        syntax,
+    } = parse_macro_input!(input);
+
+    let errors = checks.run(&syntax, &spans);
+
+    if !errors.is_empty() {
+        let errors = errors.iter().map(syn::Error::to_compile_error);
+        // Keeps the expression well-typed, so that only the errors are reported.
+        let placeholder = emit.placeholder();
+        return quote! {{ #(#errors)* #placeholder }}.into();
+    }
+
+    match emit.expand(&syntax) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => {
+            let error = error.to_compile_error();
+            let placeholder = emit.placeholder();
+            quote! {{ #error #placeholder }}.into()
+        }
+    }
 }
@@ -0,0 +1,148 @@
+use pb_ebnf_core::span::NodePosition;
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+
+/// Tokens of the meta identifiers of the macro input, by rule and node
+/// position, see [NodePosition].
+#[derive(Default)]
+pub struct Spans {
+    rules: Vec<RuleSpans>,
+}
+
+#[derive(Default)]
+struct RuleSpans {
+    lhs: TokenStream,
+    /// Meta identifiers of each alternative, in source order.
+    alternatives: Vec<Vec<TokenStream>>,
+}
+
+impl Spans {
+    /// Splits the input of the syntax into rules on `;`, the lhs from the
+    /// rhs on `=`, and the rhs into alternatives on `|`, the sequences being
+    /// part of the alternative they are nested in.
+    pub fn new(tokens: TokenStream) -> Self {
+        let mut spans = Self::default();
+        let mut rule = RuleSpans::default();
+        let mut lhs = true;
+        let mut tokens = tokens.into_iter().peekable();
+
+        while let Some(token) = tokens.peek().cloned() {
+            match &token {
+                TokenTree::Punct(punct) if punct.as_char() == ';' => {
+                    tokens.next();
+                    spans.rules.push(std::mem::take(&mut rule));
+                    lhs = true;
+                }
+                TokenTree::Punct(punct) if punct.as_char() == '=' && lhs => {
+                    tokens.next();
+                    rule.alternatives.push(Vec::default());
+                    lhs = false;
+                }
+                TokenTree::Punct(punct) if punct.as_char() == '|' && !lhs => {
+                    tokens.next();
+                    rule.alternatives.push(Vec::default());
+                }
+                _ if lhs => {
+                    tokens.next();
+                    token.to_tokens(&mut rule.lhs);
+                }
+                _ => {
+                    let alternative = rule.alternatives.last_mut().unwrap();
+                    walk(&mut tokens, alternative);
+                }
+            }
+        }
+
+        spans
+    }
+
+    /// Returns the tokens of the meta identifier at the position.
+    pub fn get(&self, position: NodePosition) -> Option<&TokenStream> {
+        match position {
+            NodePosition::Lhs { rule } => self.rules.get(rule).map(|r| &r.lhs),
+            NodePosition::Reference {
+                rule,
+                alternative,
+                index,
+            } => self
+                .rules
+                .get(rule)?
+                .alternatives
+                .get(alternative)?
+                .get(index),
+        }
+    }
+
+    /// Returns an error covering the tokens of the meta identifier, or the
+    /// macro call if there are none.
+    pub fn error(&self, position: Option<NodePosition>, message: String) -> syn::Error {
+        match position.and_then(|position| self.get(position)) {
+            Some(tokens) if !tokens.is_empty() => syn::Error::new_spanned(tokens, message),
+            _ => syn::Error::new(Span::call_site(), message),
+        }
+    }
+}
+
+/// Consumes a token of an alternative, collecting the meta identifiers it
+/// starts or contains.
+fn walk(
+    tokens: &mut std::iter::Peekable<impl Iterator<Item = TokenTree>>,
+    ids: &mut Vec<TokenStream>,
+) {
+    let Some(token) = tokens.next() else {
+        return;
+    };
+
+    match token {
+        TokenTree::Group(group) => {
+            let mut inner = group.stream().into_iter().peekable();
+
+            while inner.peek().is_some() {
+                walk(&mut inner, ids);
+            }
+        }
+        // <a b> <c> are the fragments of a single meta identifier.
+        token if is_fragment_start(&token) => {
+            let mut id = TokenStream::default();
+            let mut next = Some(token);
+
+            while let Some(token) = next.take() {
+                fragment(token, tokens, &mut id);
+
+                if tokens.peek().is_some_and(is_fragment_start) {
+                    next = tokens.next();
+                }
+            }
+
+            ids.push(id);
+        }
+        _ => {}
+    }
+}
+
+fn is_fragment_start(token: &TokenTree) -> bool {
+    match token {
+        TokenTree::Punct(punct) => punct.as_char() == '<',
+        TokenTree::Ident(_) => true,
+        _ => false,
+    }
+}
+
+/// Collects the tokens of a fragment, up to the closing `>`.
+fn fragment(token: TokenTree, tokens: &mut impl Iterator<Item = TokenTree>, id: &mut TokenStream) {
+    let open = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '<');
+    token.to_tokens(id);
+
+    if !open {
+        return;
+    }
+
+    for token in tokens.by_ref() {
+        let close = matches!(&token, TokenTree::Punct(punct) if punct.as_char() == '>');
+        token.to_tokens(id);
+
+        if close {
+            return;
+        }
+    }
+}
@@ -1,6 +1,50 @@
 pub use pb_ebnf_core::*;
-pub use pb_ebnf_macros::ebnf;
 
-pub mod regex {
-    use super::{ebnf, StaticSyntax};
-}
+/// The checks of the macro reject the syntax at compile time.
+///
+/// Duplicate rules:
+///
+/// ```compile_fail
+/// use pb_ebnf::{ebnf, StaticSyntax};
+///
+/// const SYNTAX: StaticSyntax = ebnf! {
+///     <digit> = '0';
+///     <digit> = '1';
+/// };
+/// ```
+///
+/// Undefined symbols:
+///
+/// ```compile_fail
+/// use pb_ebnf::{ebnf, StaticSyntax};
+///
+/// const SYNTAX: StaticSyntax = ebnf! {
+///     #![deny(undefined_symbols)]
+///     <number> = <digit>, { <digit> };
+/// };
+/// ```
+///
+/// Left recursion:
+///
+/// ```compile_fail
+/// use pb_ebnf::{ebnf, StaticSyntax};
+///
+/// const SYNTAX: StaticSyntax = ebnf! {
+///     #![deny(left_recursion)]
+///     <list> = <list>, ',', <digit> | <digit>;
+///     <digit> = '0' | '1';
+/// };
+/// ```
+///
+/// Exceptions referencing a recursive rule:
+///
+/// ```compile_fail
+/// use pb_ebnf::{ebnf, StaticSyntax};
+///
+/// const SYNTAX: StaticSyntax = ebnf! {
+///     <word> = <letters> - <nested>;
+///     <letters> = 'x', { 'x' };
+///     <nested> = 'x' | '(', <nested>, ')';
+/// };
+/// ```
+pub use pb_ebnf_macros::ebnf;
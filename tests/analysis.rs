@@ -0,0 +1,75 @@
+use pb_ebnf::{
+    analysis::{Analysis, Lookahead, LookaheadSet},
+    ebnf, StaticSyntax,
+};
+
+const SYNTAX: StaticSyntax = ebnf! {
+    <list> = '[', [<items>], ']';
+    <items> = <item>, { ',', <item> };
+    <item> = 0 * "never", (<number> | <list>);
+    <number> = { <digit> }, 2 * <digit>;
+    <digit> = '0' | '1';
+};
+
+const NULLABLE: StaticSyntax = ebnf! {
+    <sequence> = <optional>, <repeated>, <never>, <empty>;
+    <optional> = ['a'];
+    <repeated> = {'a'};
+    <never> = 0 * 'a';
+    <empty> = 'a' | ;
+};
+
+fn set<const N: usize>(literals: [&str; N]) -> LookaheadSet {
+    literals
+        .into_iter()
+        .map(|l| Lookahead::Literal(l.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_nullable() {
+    let analysis = Analysis::new(&SYNTAX);
+
+    assert!(!analysis.is_nullable("list"));
+    assert!(!analysis.is_nullable("item"));
+    assert!(!analysis.is_nullable("number"));
+
+    let analysis = Analysis::new(&NULLABLE);
+
+    for rule in ["sequence", "optional", "repeated", "never", "empty"] {
+        assert!(analysis.is_nullable(rule), "{rule}");
+    }
+}
+
+#[test]
+fn test_iter_rules() {
+    let analysis = Analysis::new(&SYNTAX);
+
+    let rules = analysis
+        .iter_rules()
+        .map(|(lhs, _)| lhs)
+        .collect::<Vec<_>>();
+    assert_eq!(rules, ["list", "items", "item", "number", "digit"]);
+}
+
+#[test]
+fn test_first() {
+    let analysis = Analysis::new(&SYNTAX);
+
+    assert_eq!(analysis.first("list"), Some(&set(["["])));
+    assert_eq!(analysis.first("items"), Some(&set(["0", "1", "["])));
+    assert_eq!(analysis.first("item"), Some(&set(["0", "1", "["])));
+}
+
+#[test]
+fn test_follow() {
+    let analysis = Analysis::new(&SYNTAX);
+
+    let mut list = set([",", "]"]);
+    list.insert(Lookahead::End);
+
+    assert_eq!(analysis.follow("list"), Some(&list));
+    assert_eq!(analysis.follow("items"), Some(&set(["]"])));
+    assert_eq!(analysis.follow("item"), Some(&set([",", "]"])));
+    assert_eq!(analysis.follow("digit"), Some(&set([",", "0", "1", "]"])));
+}
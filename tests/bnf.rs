@@ -0,0 +1,133 @@
+use pb_ebnf::{
+    bnf::{BnfLowering, BnfLoweringOptions, BnfSyntax, BnfTerm, LoweringError, Origin, Recursion},
+    ebnf,
+    glr::GlrParser,
+    lr::Automaton,
+    prelude::*,
+    StaticSyntax,
+};
+
+const IDENTIFIER: StaticSyntax = ebnf! {
+    <statement> = <identifier>, ';';
+    <identifier> = ( <letter>, { <letter> } ) - "END";
+};
+
+const STATEMENT: StaticSyntax = ebnf! {
+    <Statement> = "let", <name>, [ '=', <value> ], { ',', ( <name> | <value> ) };
+    <Statement__opt_0> = 'x';
+};
+
+#[test]
+fn test_anonymous_rules() {
+    let lowering = BnfLowering::try_from(STATEMENT.to_owned()).unwrap();
+    let names = lowering
+        .syntax
+        .iter()
+        .map(|rule| rule.lhs.to_string())
+        .collect::<Vec<_>>();
+
+    // Statement__opt_0 is taken.
+    assert_eq!(
+        names,
+        [
+            "Statement",
+            "Statement__opt_1",
+            "Statement__grp_3",
+            "Statement__rep_2",
+            "Statement__opt_0"
+        ]
+    );
+
+    let origin = |term| Origin {
+        rule: "Statement".to_string(),
+        alternative: 0,
+        term,
+    };
+
+    assert_eq!(lowering.origins.len(), 3);
+    assert_eq!(lowering.origins["Statement__opt_1"], origin(2));
+    assert_eq!(lowering.origins["Statement__rep_2"], origin(3));
+    assert_eq!(lowering.origins["Statement__grp_3"], origin(3));
+    assert_eq!(lowering.origin("Statement__grp_3"), "Statement");
+    assert_eq!(lowering.origin("Statement__opt_0"), "Statement__opt_0");
+}
+
+const LIST: StaticSyntax = ebnf! {
+    <list> = '(', [ <item> ], { ',', <item> }, ')' | '[', { ',', <item> }, ']';
+};
+
+/// Returns each rule as `lhs = terms | terms`.
+fn rules(lowering: &BnfLowering) -> Vec<String> {
+    lowering
+        .syntax
+        .iter()
+        .map(|rule| {
+            let defs = rule
+                .rhs
+                .iter()
+                .map(|def| {
+                    def.iter()
+                        .map(|term| match term {
+                            BnfTerm::Symbol(symbol) => symbol.to_string(),
+                            BnfTerm::Literal(literal) => format!("'{}'", &**literal),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>();
+
+            format!("{} = {}", &*rule.lhs, defs.join(" | "))
+        })
+        .collect()
+}
+
+#[test]
+fn test_options() {
+    let options = BnfLoweringOptions::default()
+        .repetition(Recursion::Right)
+        .inline_optionals(true)
+        .deduplicate(true);
+
+    let lowering = BnfLowering::with_options(LIST.to_owned(), options).unwrap();
+
+    assert_eq!(
+        rules(&lowering),
+        [
+            "list = '(' item list__rep_0 ')' | '(' list__rep_0 ')' | '[' list__rep_0 ']'",
+            "list__rep_0 =  | ',' item list__rep_0",
+        ]
+    );
+
+    let lowering = BnfLowering::try_from(LIST.to_owned()).unwrap();
+
+    assert_eq!(
+        rules(&lowering),
+        [
+            "list = '(' list__opt_0 list__rep_1 ')' | '[' list__rep_2 ']'",
+            "list__opt_0 =  | item",
+            "list__rep_1 =  | list__rep_1 ',' item",
+            "list__rep_2 =  | list__rep_2 ',' item",
+        ]
+    );
+}
+
+#[test]
+fn test_exception() {
+    let error = LoweringError::Exception("identifier".to_string());
+
+    assert_eq!(
+        BnfLowering::try_from(IDENTIFIER.to_owned()).unwrap_err(),
+        error
+    );
+    assert_eq!(
+        BnfSyntax::try_from(IDENTIFIER.to_owned()).unwrap_err(),
+        error
+    );
+    assert_eq!(Automaton::lalr1(&IDENTIFIER).unwrap_err(), error);
+    assert!(GlrParser::new(&IDENTIFIER).is_err());
+
+    assert_eq!(
+        error.to_string(),
+        "<identifier> contains an exception, which BNF cannot express"
+    );
+}
@@ -0,0 +1,41 @@
+use pb_ebnf::{
+    bnf::BnfSyntax,
+    cnf::{cyk, is_cnf, ToCnf},
+    ebnf,
+    prelude::*,
+    StaticSyntax,
+};
+
+const BALANCED: StaticSyntax = ebnf! {
+    <balanced> = { '(', <balanced>, ')' };
+};
+
+const LIST: StaticSyntax = ebnf! {
+    <list> = '[', [<items>], ']';
+    <items> = <item>, { ',', <item> };
+    <item> = <number> | <list>;
+};
+
+#[test]
+fn test_to_cnf() {
+    for syntax in [BALANCED, LIST] {
+        let bnf = BnfSyntax::try_from(syntax.to_owned()).unwrap();
+        assert!(!is_cnf(&bnf));
+        assert!(is_cnf(&bnf.to_cnf()));
+    }
+}
+
+#[test]
+fn test_cyk() {
+    let cnf = BnfSyntax::try_from(BALANCED.to_owned()).unwrap().to_cnf();
+
+    assert!(cyk(&cnf, [""; 0]));
+    assert!(cyk(&cnf, ["(", ")", "(", "(", ")", ")"]));
+    assert!(!cyk(&cnf, ["(", ")", ")"]));
+
+    let cnf = BnfSyntax::try_from(LIST.to_owned()).unwrap().to_cnf();
+
+    assert!(cyk(&cnf, ["[", "]"]));
+    assert!(cyk(&cnf, ["[", "number", ",", "[", "number", "]", "]"]));
+    assert!(!cyk(&cnf, ["[", "number", ",", "]"]));
+}
@@ -0,0 +1,93 @@
+use pb_ebnf::{cst::NodeKind, earley::EarleyParser, ebnf, StaticSyntax};
+
+const AMBIGUOUS: StaticSyntax = ebnf! {
+    <expression> = <expression>, "+", <expression> | <number>;
+    <number> = <digit>, { <digit> };
+    <digit> = "1" | "2";
+};
+
+const LINES: StaticSyntax = ebnf! {
+    <lines> = <lines>, <line> | <line>;
+    <line> = [ <label> ], <word> - "END", ";";
+    <label> = <word>, ":";
+    <word> = <letter>, { <letter> };
+    <letter> = "A" | "D" | "E" | "N";
+};
+
+#[test]
+fn test_ambiguous() {
+    let parser = EarleyParser::new(&AMBIGUOUS);
+
+    let forest = parser.parse("1+2+12").unwrap();
+    assert!(forest.is_ambiguous());
+
+    let trees = forest.trees(10);
+    assert_eq!(trees.len(), 2);
+    assert!(trees.iter().all(|tree| tree.span == (0..6)));
+
+    let forest = parser.parse("12").unwrap();
+    assert!(!forest.is_ambiguous());
+    assert_eq!(forest.trees(10)[0].children[0].children.len(), 2);
+}
+
+#[test]
+fn test_left_recursion_and_exceptions() {
+    let parser = EarleyParser::new(&LINES).skip_whitespace(true);
+
+    let forest = parser.parse("AND; DE: NA ;").unwrap();
+    let trees = forest.trees(10);
+
+    assert_eq!(trees.len(), 1);
+    assert_eq!(trees[0].kind, NodeKind::Rule("lines".to_string()));
+    assert_eq!(trees[0].children[1].text("AND; DE: NA ;"), "DE: NA ;");
+
+    assert!(parser.recognise("END: AND;"));
+    assert!(!parser.recognise("A; END;"));
+}
+
+#[test]
+fn test_errors() {
+    let parser = EarleyParser::new(&AMBIGUOUS);
+
+    let error = parser.parse("1+2+").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 5));
+    assert_eq!(error.message, "expecting \"1\" or \"2\"");
+}
+
+#[test]
+fn test_long_input() {
+    const LIST: StaticSyntax = ebnf! {
+        <list> = <list>, "a" | "a";
+    };
+
+    let parser = EarleyParser::new(&LIST);
+    let input = "a".repeat(10_000);
+
+    assert!(parser.recognise(&input));
+
+    let forest = parser.parse(&input).unwrap();
+    assert_eq!(forest.node(forest.root()).span, 0..10_000);
+    assert!(!forest.is_ambiguous());
+}
+
+#[test]
+fn test_nested_exceptions() {
+    const IDENTIFIERS: StaticSyntax = ebnf! {
+        <identifiers> = <identifiers>, <identifier>, ";" | <identifier>, ";";
+        <identifier> = <word> - <keyword>;
+        <keyword> = ("I", { "F" }) - "IFF";
+        <word> = <letter>, { <letter> };
+        <letter> = "A" | "D" | "F" | "I";
+    };
+
+    let parser = EarleyParser::new(&IDENTIFIERS);
+
+    assert!(parser.recognise("AD;IFF;FI;"));
+    assert!(!parser.recognise("AD;IF;"));
+    assert!(!parser.recognise("I;"));
+    assert!(!parser.recognise("IFFF;"));
+
+    let input = "AD;IFF;".repeat(2_000);
+    let forest = parser.parse(&input).unwrap();
+    assert_eq!(forest.node(forest.root()).span, 0..input.len());
+}
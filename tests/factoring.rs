@@ -0,0 +1,30 @@
+use pb_ebnf::{ebnf, factoring, StaticSyntax};
+
+const SYNTAX: StaticSyntax = ebnf! {
+    <command> = "RUN" | "GET", "ID" | "RUN", <expression> | "GET", "#", <expression>;
+    <expression> = ( <number>, "+", <number> | <number>, "-", <number> ) | <number>;
+};
+
+#[test]
+fn test_left_factor() {
+    let factored = factoring::left_factor_syntax(&SYNTAX);
+
+    assert_eq!(factored.changed, ["command", "expression"]);
+    assert_eq!(
+        factored.syntax.to_string(),
+        [
+            "<command> = \"RUN\", [<expression>] | \"GET\", (\"ID\" | \"#\", <expression>);",
+            "<expression> = (<number>, (\"+\", <number> | \"-\", <number>)) | <number>;",
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_unchanged() {
+    let syntax = "<a> = 'x', <a> | 'y';".parse::<pb_ebnf::Syntax>().unwrap();
+    let factored = factoring::left_factor_syntax(&syntax);
+
+    assert!(factored.changed.is_empty());
+    assert_eq!(factored.syntax, syntax);
+}
@@ -0,0 +1,72 @@
+use pb_ebnf::{
+    earley::EarleyParser,
+    ebnf,
+    generator::{Generator, Rng},
+    StaticSyntax,
+};
+
+const EXPR: StaticSyntax = ebnf! {
+    <expr> = <term>, { ('+' | '-'), <term> };
+    <term> = <number> | '(', <expr>, ')' | [ '-' ], <term>;
+};
+
+const WORDS: StaticSyntax = ebnf! {
+    <word> = <letters> - "END";
+    <letters> = ( 'E' | 'N' | 'D' ), [ <letters> ];
+};
+
+fn number(input: &str) -> Option<usize> {
+    let len = input.chars().take_while(char::is_ascii_digit).count();
+    (len > 0).then_some(len)
+}
+
+#[test]
+fn test_generate() {
+    let parser = EarleyParser::new(&EXPR).terminal("number", number);
+
+    for seed in 0..64 {
+        let mut generator = Generator::new(&EXPR)
+            .terminal("number", |rng| rng.below(100).to_string())
+            .seed(seed)
+            .max_depth(6);
+
+        let sentence = generator.generate().unwrap();
+        assert!(parser.recognise(&sentence), "{sentence}");
+
+        // The nesting of parentheses is bounded by the maximum depth.
+        let nesting = sentence.chars().fold((0, 0), |(depth, max), c| match c {
+            '(' => (depth + 1, max.max(depth + 1)),
+            ')' => (depth - 1, max),
+            _ => (depth, max),
+        });
+        assert!(nesting.1 <= 3, "{sentence}");
+    }
+}
+
+#[test]
+fn test_seed() {
+    let generate = |seed| {
+        let mut generator = Generator::new(&EXPR).seed(seed).separator(" ");
+        (0..8)
+            .map(|_| generator.generate().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(generate(42), generate(42));
+    assert_ne!(generate(42), generate(43));
+}
+
+#[test]
+fn test_exception() {
+    for seed in 0..32 {
+        let mut generator = Generator::new(&WORDS).seed(seed);
+        let word = generator.generate_rule("word").unwrap();
+        assert_ne!(word, "END");
+    }
+}
+
+#[test]
+#[should_panic(expected = "the range 0..0 is empty")]
+fn test_below_zero() {
+    Rng::new(0).below(0);
+}
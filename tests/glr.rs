@@ -0,0 +1,47 @@
+use pb_ebnf::{cst::NodeKind, ebnf, glr::GlrParser, StaticSyntax};
+
+const AMBIGUOUS: StaticSyntax = ebnf! {
+    <sum> = <sum>, '+', <sum> | <number>;
+};
+
+const HIDDEN_LEFT_RECURSION: StaticSyntax = ebnf! {
+    <s> = [<prefix>], <s>, 'b' | 'x';
+    <prefix> = 'p';
+};
+
+#[test]
+fn test_ambiguous() {
+    let parser = GlrParser::new(&AMBIGUOUS).unwrap();
+    assert!(!parser.automaton().is_conflict_free());
+
+    let forest = parser
+        .parse(["number", "+", "number", "+", "number"])
+        .unwrap();
+    assert!(forest.is_ambiguous());
+
+    let trees = forest.trees(10);
+    assert_eq!(trees.len(), 2);
+    assert!(trees.iter().all(|tree| tree.span == (0..5)));
+    assert_ne!(trees[0], trees[1]);
+
+    let forest = parser.parse(["number"]).unwrap();
+    assert!(!forest.is_ambiguous());
+}
+
+#[test]
+fn test_hidden_left_recursion() {
+    let parser = GlrParser::new(&HIDDEN_LEFT_RECURSION).unwrap();
+
+    let trees = parser.parse(["x", "b", "b"]).unwrap().trees(10);
+    assert_eq!(trees.len(), 1);
+    assert_eq!(trees[0].children[0].kind, NodeKind::Rule("s".to_string()));
+    assert_eq!(trees[0].children[0].span, 0..2);
+
+    // The prefix applies to either <s>.
+    let trees = parser.parse(["p", "x", "b", "b"]).unwrap().trees(10);
+    assert_eq!(trees.len(), 2);
+
+    let error = parser.parse(["x", "x"]).unwrap_err();
+    assert_eq!(error.position, 1);
+    assert_eq!(error.expected, vec![None, Some("b".to_string())]);
+}
@@ -0,0 +1,57 @@
+use pb_ebnf::{
+    earley::EarleyParser,
+    ebnf,
+    gnf::{to_gnf, GnfError},
+    Primary, StaticSyntax,
+};
+
+const EXPR: StaticSyntax = ebnf! {
+    <expr> = <expr>, '+', <term> | <term>;
+    <term> = <number> | '(', <expr>, ')' | [ '-' ], <term>, '!';
+};
+
+#[test]
+fn test_to_gnf() {
+    let gnf = to_gnf(&EXPR).unwrap();
+    let defined = gnf.syntax.iter().map(|rule| &*rule.lhs).collect::<Vec<_>>();
+
+    for rule in gnf.syntax.iter() {
+        for def in rule.rhs.iter() {
+            assert!(!matches!(
+                def[0].primary,
+                Primary::MetaIdentifier(ref id) if defined.contains(&&**id)
+            ));
+            assert!(def[1..].iter().all(|term| matches!(
+                term.primary,
+                Primary::MetaIdentifier(ref id) if defined.contains(&&**id)
+            )));
+        }
+    }
+
+    // <expr> is used on a rhs, the start rule is a new one.
+    assert_eq!(&*gnf.syntax[0].lhs, "expr_start");
+    assert_eq!(gnf.origin("expr_start").map(|id| &**id), Some("expr"));
+    assert_eq!(gnf.origin("expr").map(|id| &**id), Some("expr"));
+    assert_eq!(gnf.origin("expr_rep").map(|id| &**id), Some("expr"));
+
+    let number = |input: &str| input.starts_with('1').then_some(1);
+    let original = EarleyParser::new(&EXPR).terminal("number", number);
+    let converted = EarleyParser::new(&gnf.syntax).terminal("number", number);
+
+    for input in ["1", "1+1", "(1+-1!)+1", "--1!!", "1+", "(1", "-1", ""] {
+        assert_eq!(
+            original.recognise(input),
+            converted.recognise(input),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn test_exception() {
+    let syntax = "<a> = <b> - 'x'; <b> = 'x' | 'y';"
+        .parse::<pb_ebnf::Syntax>()
+        .unwrap();
+
+    assert_eq!(to_gnf(&syntax), Err(GnfError::Exception("a".to_string())));
+}
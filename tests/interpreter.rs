@@ -0,0 +1,92 @@
+use pb_ebnf::{cst::NodeKind, ebnf, interpreter::Interpreter, left_recursion, StaticSyntax};
+
+const SYNTAX: StaticSyntax = ebnf! {
+    <statement> = <assignment> | <goto>;
+    <assignment> = [ "LET" ], <identifier>, "=", <number>;
+    <goto> = "GO", "TO", <number> | "GOTO", <number>;
+    <identifier> = (<letter>, { <letter> }) - "GO";
+    <letter> = "A" | "B" | "G" | "O";
+    <number> = 2 * <digit> | <digit>;
+    <digit> = "0" | "1" | "2";
+};
+
+#[test]
+fn test_parse() {
+    let interpreter = Interpreter::new(&SYNTAX).skip_whitespace(true);
+
+    let tree = interpreter.parse("LET AB = 12").unwrap();
+    let assignment = &tree.children[0];
+
+    assert_eq!(assignment.rule_name(), Some("assignment"));
+    assert_eq!(
+        assignment.children[0].kind,
+        NodeKind::Literal("LET".to_string())
+    );
+    assert_eq!(assignment.iter_rules("identifier").count(), 1);
+    assert_eq!(assignment.children[3].text("LET AB = 12"), "12");
+
+    let tree = interpreter.parse("GO TO 1").unwrap();
+    assert_eq!(tree.children[0].rule_name(), Some("goto"));
+
+    assert!(interpreter.parse_rule("identifier", "GO").is_err());
+    assert!(interpreter.parse_rule("identifier", "GOA").is_ok());
+}
+
+#[test]
+fn test_errors() {
+    let interpreter = Interpreter::new(&SYNTAX).skip_whitespace(true);
+
+    let error = interpreter.parse("LET A = 3").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 9));
+    assert_eq!(error.message, "expecting \"0\" or \"1\" or \"2\"");
+
+    assert!(interpreter.parse_rule("nowhere", "").is_err());
+}
+
+#[test]
+fn test_terminals() {
+    const SUM: StaticSyntax = ebnf! {
+        <sum> = <Integer>, { "+", <Integer> };
+    };
+
+    let interpreter = Interpreter::new(&SUM).terminal("Integer", |input| {
+        Some(input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len())
+            .filter(|&len| len > 0)
+    });
+
+    let tree = interpreter.parse("12+3+45").unwrap();
+    assert_eq!(tree.children.len(), 5);
+    assert_eq!(
+        tree.children[4].kind,
+        NodeKind::Terminal("Integer".to_string())
+    );
+    assert!(interpreter.parse("12+").is_err());
+}
+
+#[test]
+fn test_long_repetition() {
+    const WORD: StaticSyntax = ebnf! {
+        <word> = { 'a' }, 'b';
+    };
+
+    let input = format!("{}b", "a".repeat(10_000));
+    let tree = Interpreter::new(&WORD).parse(&input).unwrap();
+
+    assert_eq!(tree.children.len(), 10_001);
+}
+
+#[test]
+fn test_eliminated_left_recursion() {
+    const SUM: StaticSyntax = ebnf! {
+        <sum> = <sum>, "+", <digit> | <digit>;
+        <digit> = "1" | "2";
+    };
+
+    assert!(Interpreter::new(&SUM).parse("1+2").is_err());
+
+    let interpreter = Interpreter::new(&left_recursion::eliminate(&SUM));
+    let tree = interpreter.parse("1+2+1").unwrap();
+
+    assert_eq!(tree.rule_name(), Some("sum"));
+    assert_eq!(tree.iter_rules("digit").count(), 3);
+}
@@ -0,0 +1,54 @@
+use pb_ebnf::{ebnf, left_recursion, StaticSyntax};
+
+const SYNTAX: StaticSyntax = ebnf! {
+    <lines> = <lines>, <line> | <line>;
+    <line> = <number>, <statement>;
+    <regex> = <union> | <simple>;
+    <union> = [<blank>], <regex>, '|', <simple>;
+    <blank> = { ' ' };
+    <simple> = 'a' | 'b';
+};
+
+#[test]
+fn test_cycles() {
+    let cycles = left_recursion::find_cycles(&SYNTAX);
+
+    assert_eq!(cycles.len(), 2);
+
+    assert!(cycles[0].is_direct());
+    assert_eq!(cycles[0].cycle, ["lines"]);
+
+    assert_eq!(cycles[1].cycle, ["regex", "union"]);
+    assert_eq!(cycles[1].to_string(), "<regex> -> <union> -> <regex>");
+}
+
+#[test]
+fn test_nullable_prefix() {
+    let syntax = "<a> = <b>, <a>, 'x' | 'y'; <b> = ['z'];"
+        .parse::<pb_ebnf::Syntax>()
+        .unwrap();
+
+    assert!(left_recursion::is_left_recursive(&syntax));
+    assert!(!left_recursion::is_left_recursive(
+        &"<a> = 'x', <a> | 'y';".parse::<pb_ebnf::Syntax>().unwrap()
+    ));
+}
+
+#[test]
+fn test_eliminate() {
+    let syntax = left_recursion::eliminate(&SYNTAX);
+
+    assert!(!left_recursion::is_left_recursive(&syntax));
+    assert_eq!(
+        syntax.to_string(),
+        [
+            "<lines> = <line>, {<line>};",
+            "<line> = <number>, <statement>;",
+            "<regex> = <union> | <simple>;",
+            "<union> = (\" \", {\" \"}, <regex>, \"|\", <simple> | <simple>, \"|\", <simple>), {\"|\", <simple>};",
+            "<blank> = {\" \"};",
+            "<simple> = \"a\" | \"b\";",
+        ]
+        .join("\n")
+    );
+}
@@ -0,0 +1,38 @@
+use pb_ebnf::{bnf::BnfSyntax, ebnf, lifting::lift, prelude::*, StaticSyntax, Syntax};
+
+// Plain BNF, as found in textbooks.
+const TEXTBOOK: StaticSyntax = ebnf! {
+    <call> = <name>, "(", <args>, ")" | <name>;
+    <args> = | <arg list>;
+    <arg list> = <expr> | <arg list>, ",", <expr>;
+    <expr> = <expr>, "+", <term> | <term>;
+    <term> = <factor>, "*", <term> | <factor>;
+    <factor> = <number> | "(", <expr>, ")";
+};
+
+const LIST: StaticSyntax = ebnf! {
+    <list> = "(", [ <item> ], { ",", <item> }, ")";
+};
+
+#[test]
+fn test_lift() {
+    let bnf = BnfSyntax::try_from(TEXTBOOK.to_owned()).unwrap();
+
+    // <args> and <arg list> are used once, and inlined.
+    assert_eq!(
+        lift(&bnf).to_string(),
+        [
+            r#"<call> = <name>, ["(", [<expr>, {",", <expr>}], ")"];"#,
+            r#"<expr> = <term>, {"+", <term>};"#,
+            r#"<term> = {<factor>, "*"}, <factor>;"#,
+            r#"<factor> = <number> | "(", <expr>, ")";"#,
+        ]
+        .join("\n")
+    );
+}
+
+#[test]
+fn test_round_trip() {
+    let bnf = BnfSyntax::try_from(LIST.to_owned()).unwrap();
+    assert_eq!(Syntax::from(bnf), LIST.to_owned());
+}
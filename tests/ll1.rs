@@ -0,0 +1,59 @@
+use pb_ebnf::{
+    analysis::{Lookahead, LookaheadSet},
+    ebnf, ll1, StaticSyntax,
+};
+
+const LL1: StaticSyntax = ebnf! {
+    <list> = '[', [<items>], ']';
+    <items> = <item>, { ',', <item> };
+    <item> = <number> | <list>;
+    <number> = '0' | '1';
+};
+
+const NOT_LL1: StaticSyntax = ebnf! {
+    <statement> = <call> | <assignment>;
+    <call> = <identifier>, '(', ')';
+    <assignment> = <identifier>, '=', <value>, [<suffix>];
+    <suffix> = '=', <value>;
+};
+
+#[test]
+fn test_ll1_grammar() {
+    assert!(ll1::is_ll1(&LL1));
+}
+
+#[test]
+fn test_conflicts() {
+    let conflicts = ll1::check(&NOT_LL1);
+
+    assert_eq!(conflicts.len(), 1);
+
+    let conflict = &conflicts[0];
+    assert_eq!(conflict.rule, "statement");
+    assert_eq!(conflict.context, None);
+    assert_eq!((conflict.first.index, conflict.second.index), (0, 1));
+    assert_eq!(conflict.first.text, "<call>");
+    assert_eq!(conflict.second.text, "<assignment>");
+    assert_eq!(
+        conflict.lookaheads,
+        LookaheadSet::from([Lookahead::Terminal("identifier".to_string())])
+    );
+    assert_eq!(
+        conflict.to_string(),
+        "<statement>: alternatives #0 (<call>) and #1 (<assignment>) are both predicted by <identifier>"
+    );
+}
+
+#[test]
+fn test_optional_conflict() {
+    let syntax = "<a> = <b>, '='; <b> = 'x', ['=', 'y'];"
+        .parse::<pb_ebnf::Syntax>()
+        .unwrap();
+
+    let conflicts = ll1::check(&syntax);
+
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].rule, "b");
+    assert_eq!(conflicts[0].context.as_deref(), Some("[\"=\", \"y\"]"));
+    assert_eq!(conflicts[0].second.text, "");
+}
@@ -0,0 +1,91 @@
+use pb_ebnf::{
+    analysis::{Lookahead, LookaheadRef},
+    cst::Node,
+    ebnf,
+    ll1_table::{IParseTable, Ll1Grammar, ParseTable, TableError},
+    StaticSyntax,
+};
+
+const GRAMMAR: Ll1Grammar<'static> = ebnf! {
+    #![emit(ll1_table)]
+    <list> = '[', [<items>], ']';
+    <items> = <item>, { ',', <item> };
+    <item> = <number> | <list>;
+};
+
+const NOT_LL1: StaticSyntax = ebnf! {
+    <statement> = <call> | <assignment>;
+    <call> = <identifier>, '(', ')';
+    <assignment> = <identifier>, '=', <value>;
+};
+
+const TYPES: StaticSyntax = ebnf! {
+    <number> = "Integer" | <Integer>;
+};
+
+#[test]
+fn test_emitted_table() {
+    let tree = GRAMMAR
+        .table
+        .parse(["[", "number", ",", "[", "]", "]"])
+        .unwrap();
+
+    let item = |children| Node::rule("item", 0..0, children);
+    assert_eq!(tree.rule_name(), Some("list"));
+    assert_eq!(tree.span, 0..6);
+
+    let items = tree.iter_rules("items").next().unwrap();
+    assert_eq!(items.span, 1..5);
+    assert_eq!(items.children.len(), 3);
+    assert_eq!(
+        items.children[0],
+        Node {
+            span: 1..2,
+            ..item(vec![Node::terminal("number", 1..2)])
+        }
+    );
+
+    let built = ParseTable::build(&GRAMMAR.syntax).unwrap();
+    assert_eq!(
+        built.lookup(0, LookaheadRef::Literal("[")),
+        GRAMMAR.table.lookup(0, LookaheadRef::Literal("["))
+    );
+    assert_eq!(built.entries.len(), GRAMMAR.table.entries.len());
+}
+
+#[test]
+fn test_driver_error() {
+    let error = GRAMMAR.table.parse(["[", "number", "number"]).unwrap_err();
+
+    assert_eq!(error.position, 2);
+    assert_eq!(error.found.as_deref(), Some("number"));
+    assert_eq!(
+        error.expected,
+        vec![Some(",".to_string()), Some("]".to_string())]
+    );
+    assert_eq!(
+        error.to_string(),
+        "unexpected \"number\" at token 2, expecting \",\" or \"]\""
+    );
+}
+
+#[test]
+fn test_collisions() {
+    let Err(TableError::Collisions(collisions)) = ParseTable::build(&NOT_LL1) else {
+        panic!("expecting collisions");
+    };
+
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].rule, "statement");
+    assert_eq!(
+        collisions[0].lookahead,
+        Lookahead::Terminal("identifier".to_string())
+    );
+    assert_eq!(collisions[0].productions.len(), 2);
+
+    // A literal does not collide with a terminal of the same name.
+    let table = ParseTable::build(&TYPES).unwrap();
+    let literal = table.lookup(0, LookaheadRef::Literal("Integer"));
+    let terminal = table.lookup(0, LookaheadRef::Terminal("Integer"));
+    assert!(literal.is_some() && terminal.is_some() && literal != terminal);
+}
@@ -0,0 +1,69 @@
+use pb_ebnf::{
+    analysis::Lookahead,
+    ebnf,
+    lr::{Automaton, ConflictKind},
+    StaticSyntax,
+};
+
+const EXPR: StaticSyntax = ebnf! {
+    <expr> = <expr>, '+', <term> | <term>;
+    <term> = <number> | '(', <expr>, ')';
+};
+
+const AMBIGUOUS: StaticSyntax = ebnf! {
+    <sum> = <sum>, ('+' | '-'), <sum> | <number>;
+};
+
+const NOT_LALR: StaticSyntax = ebnf! {
+    <s> = 'a', <e>, 'c' | 'a', <f>, 'd' | 'b', <f>, 'c' | 'b', <e>, 'd';
+    <e> = 'e';
+    <f> = 'e';
+};
+
+#[test]
+fn test_left_recursion() {
+    let automaton = Automaton::lalr1(&EXPR).unwrap();
+    assert!(automaton.is_conflict_free());
+
+    let tree = automaton
+        .parse(["number", "+", "(", "number", ")", "+", "number"])
+        .unwrap();
+
+    assert_eq!(tree.rule_name(), Some("expr"));
+    assert_eq!(tree.span, 0..7);
+
+    // Associates to the left.
+    let lhs = tree.iter_rules("expr").next().unwrap();
+    assert_eq!(lhs.span, 0..5);
+
+    let error = automaton.parse(["number", "+"]).unwrap_err();
+    assert_eq!(error.position, 2);
+    assert_eq!(error.found, None);
+}
+
+#[test]
+fn test_conflicts() {
+    let conflicts = Automaton::lalr1(&AMBIGUOUS).unwrap().conflicts();
+
+    assert_eq!(conflicts.len(), 2);
+    assert!(conflicts.iter().all(|conflict| {
+        conflict.kind == ConflictKind::ShiftReduce && conflict.rules == ["sum"]
+    }));
+    assert_eq!(conflicts[0].lookahead, Lookahead::Literal("+".to_string()));
+    assert_eq!(
+        conflicts[0].to_string(),
+        "shift/reduce conflict in state 6 on \"+\": <sum> = <sum> <sum__grp_0> <sum> • | <sum__grp_0> = • \"+\""
+    );
+}
+
+#[test]
+fn test_lr1() {
+    assert!(Automaton::lr1(&NOT_LALR).unwrap().is_conflict_free());
+
+    let conflicts = Automaton::lalr1(&NOT_LALR).unwrap().conflicts();
+    assert_eq!(conflicts.len(), 2);
+    assert!(conflicts
+        .iter()
+        .all(|conflict| conflict.kind == ConflictKind::ReduceReduce));
+    assert_eq!(conflicts[0].items, ["<e> = \"e\" •", "<f> = \"e\" •"]);
+}
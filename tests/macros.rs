@@ -0,0 +1,30 @@
+use pb_ebnf::{ebnf, prelude::*, IndexedSyntax, StaticSyntax, SyntaxRef};
+
+const CHECKED_SYNTAX: StaticSyntax = ebnf! {
+    #![deny(undefined_symbols, left_recursion)]
+    <number> = <digit>, { <digit> };
+    <digit> = '0' | '1';
+};
+
+#[test]
+fn test_checked_syntax() {
+    assert_eq!(CHECKED_SYNTAX.iter_non_terminals().count(), 2);
+}
+
+#[test]
+fn test_rule_index() {
+    let rule = CHECKED_SYNTAX.get("digit").unwrap();
+    assert_eq!(&*rule.lhs, "digit");
+    assert_eq!(rule.rhs.as_ref().len(), 2);
+    assert!(CHECKED_SYNTAX.get("letter").is_none());
+
+    // Without an index, the rules are scanned.
+    let unindexed = SyntaxRef::new(CHECKED_SYNTAX.as_ref());
+    assert_eq!(unindexed, CHECKED_SYNTAX);
+    assert_eq!(unindexed.get("digit"), Some(rule));
+
+    let owned = IndexedSyntax::from(CHECKED_SYNTAX.to_owned());
+    assert_eq!(owned.get("digit"), Some(&rule.to_owned()));
+    assert_eq!(owned.position("number"), Some(0));
+    assert!(owned.get("letter").is_none());
+}
@@ -0,0 +1,64 @@
+use pb_ebnf::{cst::NodeKind, ebnf, packrat::Packrat, StaticSyntax, Syntax};
+
+const ORDERED: StaticSyntax = ebnf! {
+    <statement> = <keyword> | <identifier>;
+    <keyword> = "IF" | "IFF";
+    <identifier> = <letter> - "IF", { <letter> };
+    <letter> = "F" | "I" | "X";
+};
+
+const SUM: StaticSyntax = ebnf! {
+    <sum> = <sum>, "+", <digit> | <digit>;
+    <digit> = "1" | "2";
+};
+
+#[test]
+fn test_ordered_choice() {
+    let packrat = Packrat::new(&ORDERED);
+
+    let tree = packrat.parse("IF").unwrap();
+    assert_eq!(tree.children[0].rule_name(), Some("keyword"));
+
+    // "IF" wins over "IFF" and the rest of the input is left.
+    assert!(packrat.parse("IFF").is_err());
+
+    // <letter> - "IF" is a negative lookahead.
+    let tree = packrat.parse("XIF").unwrap();
+    assert_eq!(tree.children[0].rule_name(), Some("identifier"));
+    assert!(packrat.parse_rule("identifier", "IFX").is_err());
+}
+
+#[test]
+fn test_left_recursion() {
+    assert!(Packrat::new(&SUM).parse("1+2").is_err());
+
+    let packrat = Packrat::new(&SUM).left_recursion(true).memo_limit(16);
+    let tree = packrat.parse("1+2+1").unwrap();
+
+    // ((1 + 2) + 1)
+    assert_eq!(tree.span, 0..5);
+    assert_eq!(tree.children[0].span, 0..3);
+    assert_eq!(tree.children[0].children[0].span, 0..1);
+    assert_eq!(tree.children[2].kind, NodeKind::Rule("digit".to_string()));
+}
+
+#[test]
+fn test_errors() {
+    let packrat = Packrat::new(&SUM).left_recursion(true);
+
+    let error = packrat.parse("1+2+").unwrap_err();
+    assert_eq!((error.line, error.column), (1, 5));
+    assert_eq!(error.message, "expecting \"1\" or \"2\"");
+}
+
+#[test]
+fn test_owned_syntax() {
+    let syntax = "<list> = <item>, { ',', <item> }; <item> = 'a' | 'b';"
+        .parse::<Syntax>()
+        .unwrap();
+    let packrat = Packrat::new(&syntax);
+
+    let tree = packrat.parse("a,b,a").unwrap();
+    assert_eq!(tree.children.len(), 5);
+    assert!(packrat.parse("a,").is_err());
+}
@@ -0,0 +1,61 @@
+use pb_ebnf::{parser::parse_syntax, prelude::*, Primary, Syntax};
+
+const ISO_GRAMMAR: &str = r#"
+(* A small grammar using the ISO 14977 notation. *)
+digit excluding zero = "1" / "2" / "3" ! "4" | "5" | "6" | "7" | "8" | "9" .
+digit = "0" | digit excluding zero ;
+natural number = digit excluding zero, { digit } ;
+integer = "0" | [ "-" ], natural number ;
+twelve = 2 * digit ;
+letter = ? any unicode letter ? - 'x' ;
+<Step Opt> = "STEP", (/ integer /), (: letter :) | ;
+"#;
+
+#[test]
+fn test_parse_iso_syntax() {
+    let syntax = parse_syntax(ISO_GRAMMAR).unwrap();
+
+    let lhs = syntax.iter().map(|rule| &*rule.lhs).collect::<Vec<_>>();
+    assert_eq!(
+        lhs,
+        [
+            "digit excluding zero",
+            "digit",
+            "natural number",
+            "integer",
+            "twelve",
+            "letter",
+            "Step Opt"
+        ]
+    );
+
+    assert_eq!(syntax[0].rhs.len(), 9);
+    assert_eq!(syntax[4].rhs[0][0].repetition, Some(2));
+    assert!(syntax[5].rhs[0][0].exception.is_some());
+    assert!(matches!(syntax[5].rhs[0][0].primary, Primary::Special(_)));
+    assert_eq!(
+        syntax[5].rhs[0][0]
+            .primary
+            .iter_symbols()
+            .map(|symbol| &**symbol)
+            .collect::<Vec<_>>(),
+        ["any unicode letter"]
+    );
+    assert!(matches!(syntax[6].rhs[0][1].primary, Primary::Optional(_)));
+    assert!(matches!(syntax[6].rhs[0][2].primary, Primary::Repeated(_)));
+    assert!(matches!(syntax[6].rhs[1][0].primary, Primary::Empty));
+}
+
+#[test]
+fn test_parse_error_location() {
+    let err = "a = 'b';\nc = 'd' 'e';".parse::<Syntax>().unwrap_err();
+
+    assert_eq!((err.line, err.column), (2, 9));
+}
+
+#[test]
+fn test_syntax_is_send_and_sync() {
+    fn send_and_sync<T: Send + Sync>(_: T) {}
+
+    send_and_sync(parse_syntax(ISO_GRAMMAR).unwrap());
+}
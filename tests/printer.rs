@@ -0,0 +1,47 @@
+use pb_ebnf::{
+    ebnf,
+    parser::parse_syntax,
+    prelude::*,
+    printer::{MetaIdentifierStyle, Printer, PrinterOptions, QuoteStyle},
+    StaticSyntax, Syntax,
+};
+
+const SYNTAX: StaticSyntax = ebnf! {
+    <Statement> = "GET", "ID" | "GET", "#", <Integer>, ",", "ID" | "RUN", [<Expression>];
+    <Expression> = { <Term>, "+" }, 2 * <Term> | ;
+};
+
+#[test]
+fn test_display_round_trip() {
+    let text = SYNTAX.to_string();
+    let syntax = parse_syntax(&text).unwrap();
+
+    assert_eq!(syntax, SYNTAX.to_owned());
+    assert_eq!(syntax.to_string(), text);
+}
+
+#[test]
+fn test_printer_options() {
+    let printer = Printer::new(PrinterOptions {
+        max_width: 40,
+        align_alternatives: true,
+        quote_style: QuoteStyle::Single,
+        meta_identifier_style: MetaIdentifierStyle::Bare,
+    });
+
+    let syntax: Syntax = "<Step Opt> = 'STEP', <Expression> | ;".parse().unwrap();
+    assert_eq!(
+        printer.print_syntax(&syntax),
+        "Step Opt = 'STEP', Expression | ;"
+    );
+
+    let text = printer.print_rule(&SYNTAX.as_ref()[0]);
+    assert_eq!(
+        text,
+        "Statement = 'GET', 'ID'\n          | 'GET', '#', Integer, ',', 'ID'\n          | 'RUN', [Expression];"
+    );
+    assert_eq!(
+        text.parse::<pb_ebnf::Rule>().unwrap(),
+        SYNTAX.as_ref()[0].to_owned()
+    );
+}
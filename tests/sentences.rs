@@ -0,0 +1,75 @@
+use std::collections::BTreeSet;
+
+use pb_ebnf::{earley::EarleyParser, ebnf, sentences::Enumerator, StaticSyntax};
+
+const REGEX: StaticSyntax = ebnf! {
+    <regex> = <simple regex>, { '|', <simple regex> };
+    <simple regex> = <basic regex>, { <basic regex> };
+    <basic regex> = <elementary regex>, [ '*' | '+' ];
+    <elementary regex> = '(', <regex>, ')' | '.' | <char> | <set>;
+    <set> = '[', [ '^' ], <char>, { <char> }, ']';
+};
+
+const KEYWORDS: StaticSyntax = ebnf! {
+    <identifier> = <word> - ('a' | 'a', 'a');
+    <word> = 'a', { 'a' };
+};
+
+#[test]
+fn test_shortest_sentences() {
+    let shortest = Enumerator::new(&REGEX).shortest_sentences();
+
+    let sentences = |sentences: &[&[&str]]| {
+        sentences
+            .iter()
+            .map(|sentence| sentence.iter().map(|s| s.to_string()).collect())
+            .collect::<BTreeSet<Vec<String>>>()
+    };
+
+    assert_eq!(shortest["regex"], sentences(&[&["."], &["char"]]));
+    assert_eq!(shortest["set"], sentences(&[&["[", "char", "]"]]));
+
+    // The shortest sentences are after the excepted ones.
+    let shortest = Enumerator::new(&KEYWORDS).shortest("identifier");
+    assert_eq!(shortest, Some(sentences(&[&["a", "a", "a"]])));
+}
+
+#[test]
+fn test_sentences() {
+    let mut owned = Enumerator::new(&REGEX.to_owned());
+    let mut enumerator = Enumerator::new(&REGEX);
+
+    let sentences = enumerator
+        .sentences("regex", 4)
+        .unwrap()
+        .cloned()
+        .collect::<Vec<_>>();
+    assert!(owned.sentences("regex", 4).unwrap().eq(sentences.iter()));
+
+    // By length, without duplicates.
+    assert!(sentences
+        .windows(2)
+        .all(|w| (w[0].len(), &w[0]) < (w[1].len(), &w[1])));
+    assert_eq!(
+        sentences.len(),
+        (0..=4)
+            .map(|len| enumerator.sentences_of_len("regex", len).unwrap().len())
+            .sum::<usize>()
+    );
+
+    let parser = EarleyParser::new(&REGEX)
+        .terminal("char", |input| input.starts_with('c').then_some(1))
+        .skip_whitespace(true);
+
+    for sentence in sentences.iter() {
+        let input = sentence
+            .iter()
+            .map(|terminal| if terminal == "char" { "c" } else { terminal })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        assert!(parser.recognise(&input), "{input}");
+    }
+
+    assert!(sentences.contains(&vec!["(".into(), ".".into(), "*".into(), ")".into()]));
+}
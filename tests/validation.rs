@@ -0,0 +1,105 @@
+use pb_ebnf::{
+    prelude::*,
+    validation::{Diagnostic, DiagnosticKind},
+    Rule, Syntax,
+};
+
+// The ebnf! macro rejects duplicate rules, hence the runtime parser.
+const SYNTAX: &str = r#"
+    <Print List> = <Expression>, ';', <Print List> | <Expression> |;
+    <Expression> = <Value> | <Loop>;
+    <Loop> = "(", <Loop>, ")";
+    <Orphan> = "x";
+    <Expression> = "y";
+"#;
+
+#[test]
+fn test_validate() {
+    let diagnostics = SYNTAX.parse::<Syntax>().unwrap().validate();
+
+    let expected = [
+        Diagnostic {
+            kind: DiagnosticKind::DuplicateRule {
+                name: "Expression".into(),
+                first: 1,
+            },
+            rule: 4,
+            alternative: None,
+        },
+        Diagnostic {
+            kind: DiagnosticKind::UndefinedMetaIdentifier("Value".into()),
+            rule: 1,
+            alternative: Some(0),
+        },
+        Diagnostic {
+            kind: DiagnosticKind::EmptyAlternative,
+            rule: 0,
+            alternative: Some(2),
+        },
+        Diagnostic {
+            kind: DiagnosticKind::UnreachableRule("Orphan".into()),
+            rule: 3,
+            alternative: None,
+        },
+        Diagnostic {
+            kind: DiagnosticKind::NonProductiveRule("Loop".into()),
+            rule: 2,
+            alternative: None,
+        },
+    ];
+
+    assert_eq!(diagnostics, expected);
+}
+
+#[test]
+fn test_recursive_exception() {
+    let syntax = r#"
+        <Statement> = <Word> - <Keyword> | <Word> - ( "(", <Nested>, ")" );
+        <Keyword> = "IF" | "DO";
+        <Nested> = "x" | "(", <Nested>, ")";
+        <Word> = "x", { "x" };
+    "#
+    .parse::<Syntax>()
+    .unwrap();
+
+    let diagnostics = syntax.validate();
+
+    assert_eq!(
+        diagnostics,
+        [Diagnostic {
+            kind: DiagnosticKind::RecursiveException("Nested".into()),
+            rule: 0,
+            alternative: Some(1),
+        }]
+    );
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "rule #0, alternative #1: the exception references the recursive rule <Nested>"
+    );
+}
+
+/// A syntax which cannot be cloned.
+struct Rules(Vec<Rule>);
+
+impl AsRef<[Rule]> for Rules {
+    fn as_ref(&self) -> &[Rule] {
+        &self.0
+    }
+}
+
+impl ISyntax for Rules {
+    type Rule = Rule;
+
+    fn to_owned(self) -> Syntax {
+        self.0.into_iter().collect()
+    }
+}
+
+#[test]
+fn test_validate_borrowed() {
+    let syntax = SYNTAX.parse::<Syntax>().unwrap();
+    let rules = Rules(syntax.iter().cloned().collect());
+
+    assert_eq!(rules.validate(), syntax.validate());
+    assert_eq!(rules.validate_from("Loop"), syntax.validate_from("Loop"));
+}